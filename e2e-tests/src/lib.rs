@@ -0,0 +1,171 @@
+//! A harness for end-to-end tests of the node: it starts a `--dev` node in-process with manual
+//! seal, submits signed extrinsics over its RPC and reads back events and storage.
+//!
+//! Everything runs offline on the local machine; each [`TestNode`] gets its own temporary
+//! database and ports, and stops when dropped.
+
+use codec::{Decode, Encode};
+use jsonrpsee::{
+	core::client::ClientT,
+	rpc_params,
+	ws_client::{WsClient, WsClientBuilder},
+};
+use node_template::cli::{Cli, Sealing};
+use node_template_runtime::{self as runtime, Hash, RuntimeCall, RuntimeEvent};
+use sc_cli::SubstrateCli;
+use sc_service::TaskManager;
+use sp_core::{
+	bytes::{from_hex, to_hex},
+	crypto::Ss58Codec,
+	Pair,
+};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::generic::Era;
+use std::net::TcpListener;
+
+pub use node_template_runtime::Runtime;
+
+/// A node running in this process, authoring blocks only when [`TestNode::seal`] is called.
+pub struct TestNode {
+	rpc: WsClient,
+	genesis_hash: Hash,
+	// Dropped last, so the node outlives the RPC client.
+	_task_manager: TaskManager,
+}
+
+impl TestNode {
+	/// Start a fresh development chain and connect to its RPC.
+	///
+	/// Must be called from within a multi-threaded tokio runtime, which the node's tasks run on.
+	pub async fn start() -> Self {
+		let ws_port = free_port();
+		let args = [
+			"node-template".to_string(),
+			"--dev".into(),
+			"--tmp".into(),
+			"--no-prometheus".into(),
+			"--no-telemetry".into(),
+			"--no-mdns".into(),
+			"--port".into(),
+			free_port().to_string(),
+			"--ws-port".into(),
+			ws_port.to_string(),
+			"--rpc-port".into(),
+			free_port().to_string(),
+			"--sealing".into(),
+			"manual".into(),
+		];
+		let cli = Cli::from_iter(args);
+		let config = cli
+			.create_configuration(&cli.run, tokio::runtime::Handle::current())
+			.expect("the test node's arguments are valid");
+		let task_manager = node_template::service::new_full(config, Some(Sealing::Manual))
+			.expect("the test node starts");
+
+		let rpc = WsClientBuilder::default()
+			.build(format!("ws://127.0.0.1:{}", ws_port))
+			.await
+			.expect("the test node serves RPC");
+		let genesis_hash = rpc
+			.request("chain_getBlockHash", rpc_params![0u32])
+			.await
+			.expect("the genesis block exists");
+
+		TestNode { rpc, genesis_hash, _task_manager: task_manager }
+	}
+
+	/// Sign `call` as `signer` with their next nonce, including transactions still in the pool,
+	/// and submit it. Returns the transaction hash.
+	pub async fn submit(&self, signer: Sr25519Keyring, call: impl Into<RuntimeCall>) -> Hash {
+		let nonce: u32 = self
+			.rpc
+			.request("system_accountNextIndex", rpc_params![signer.to_account_id().to_ss58check()])
+			.await
+			.expect("the nonce can be queried");
+		let extrinsic = self.sign(signer, call.into(), nonce);
+
+		self.rpc
+			.request("author_submitExtrinsic", rpc_params![to_hex(&extrinsic.encode(), false)])
+			.await
+			.expect("the transaction is valid")
+	}
+
+	/// Seal and finalize a block with the transactions in the pool, returning its hash.
+	pub async fn seal(&self) -> Hash {
+		let block: serde_json::Value = self
+			.rpc
+			.request("engine_createBlock", rpc_params![true, true, Option::<Hash>::None])
+			.await
+			.expect("a block can be sealed");
+		serde_json::from_value(block["hash"].clone()).expect("the sealed block has a hash")
+	}
+
+	/// The events deposited in block `at`.
+	pub async fn events(&self, at: Hash) -> Vec<RuntimeEvent> {
+		let key = frame_support::storage::storage_prefix(b"System", b"Events").to_vec();
+		let records: Vec<frame_system::EventRecord<RuntimeEvent, Hash>> =
+			self.storage(key, at).await.unwrap_or_default();
+		records.into_iter().map(|record| record.event).collect()
+	}
+
+	/// Read and decode the value at the raw storage `key` in block `at`.
+	pub async fn storage<T: Decode>(&self, key: Vec<u8>, at: Hash) -> Option<T> {
+		let value: Option<String> = self
+			.rpc
+			.request("state_getStorage", rpc_params![to_hex(&key, false), at])
+			.await
+			.expect("storage can be queried");
+		value.map(|value| {
+			let bytes = from_hex(&value).expect("storage is returned as hex");
+			T::decode(&mut &bytes[..]).expect("storage decodes as the requested type")
+		})
+	}
+
+	fn sign(
+		&self,
+		signer: Sr25519Keyring,
+		call: RuntimeCall,
+		nonce: u32,
+	) -> runtime::UncheckedExtrinsic {
+		let extra: runtime::SignedExtra = (
+			frame_system::CheckNonZeroSender::<Runtime>::new(),
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		);
+		let payload = runtime::SignedPayload::from_raw(
+			call.clone(),
+			extra.clone(),
+			(
+				(),
+				runtime::VERSION.spec_version,
+				runtime::VERSION.transaction_version,
+				self.genesis_hash,
+				self.genesis_hash,
+				(),
+				(),
+				(),
+			),
+		);
+		let signature = payload.using_encoded(|payload| signer.pair().sign(payload));
+
+		runtime::UncheckedExtrinsic::new_signed(
+			call,
+			signer.to_account_id().into(),
+			runtime::Signature::Sr25519(signature),
+			extra,
+		)
+	}
+}
+
+/// A port that was free a moment ago.
+fn free_port() -> u16 {
+	TcpListener::bind("127.0.0.1:0")
+		.and_then(|listener| listener.local_addr())
+		.map(|addr| addr.port())
+		.expect("a local port is available")
+}
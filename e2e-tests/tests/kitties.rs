@@ -0,0 +1,93 @@
+use kitties_e2e_tests::{Runtime, TestNode};
+use node_template_runtime::{AccountId, RuntimeEvent};
+use pallet_kitties::{Call as KittiesCall, Event as KittiesEvent, KittyId};
+use sp_keyring::Sr25519Keyring::{Alice, Bob, Charlie};
+
+fn kitties_events(events: Vec<RuntimeEvent>) -> Vec<KittiesEvent<Runtime>> {
+	events
+		.into_iter()
+		.filter_map(|event| match event {
+			RuntimeEvent::KittiesModule(event) => Some(event),
+			_ => None,
+		})
+		.collect()
+}
+
+async fn owner(node: &TestNode, kitty_id: KittyId, at: sp_core::H256) -> Option<AccountId> {
+	node.storage(pallet_kitties::KittyOwner::<Runtime>::hashed_key_for(kitty_id), at)
+		.await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn kitties_are_created_bred_and_traded() {
+	let node = TestNode::start().await;
+
+	node.submit(Alice, KittiesCall::create { name: *b"kitty-01" }).await;
+	node.submit(Alice, KittiesCall::create { name: *b"kitty-02" }).await;
+	let block = node.seal().await;
+	let events = kitties_events(node.events(block).await);
+	assert!(matches!(
+		events[..],
+		[
+			KittiesEvent::KittyCreated { kitty_id: 0, .. },
+			KittiesEvent::KittyCreated { kitty_id: 1, .. }
+		]
+	));
+	assert_eq!(owner(&node, 0, block).await, Some(Alice.to_account_id()));
+	assert_eq!(owner(&node, 1, block).await, Some(Alice.to_account_id()));
+
+	node.submit(Alice, KittiesCall::breed { kitty_id_1: 0, kitty_id_2: 1, name: *b"kitty-03" })
+		.await;
+	node.submit(Alice, KittiesCall::sale { kitty_id: 0 }).await;
+	let block = node.seal().await;
+	let events = kitties_events(node.events(block).await);
+	assert!(matches!(
+		events[..],
+		[
+			KittiesEvent::KittyBred { kitty_id: 2, parents: (0, 1), .. },
+			KittiesEvent::KittyOnSale { kitty_id: 0, .. }
+		]
+	));
+	let parents: Option<(KittyId, KittyId)> = node
+		.storage(pallet_kitties::KittyParents::<Runtime>::hashed_key_for(2), block)
+		.await;
+	assert_eq!(parents, Some((0, 1)));
+
+	node.submit(Bob, KittiesCall::buy { kitty_id: 0 }).await;
+	node.submit(Alice, KittiesCall::transfer { recipient: Charlie.to_account_id(), kitty_id: 2 })
+		.await;
+	let block = node.seal().await;
+	let events = kitties_events(node.events(block).await);
+	assert_eq!(events.len(), 2);
+	match &events[0] {
+		KittiesEvent::KittyBought { who, seller, kitty_id: 0, .. } => {
+			assert_eq!(who, &Bob.to_account_id());
+			assert_eq!(seller, &Alice.to_account_id());
+		},
+		event => panic!("expected the kitty to be bought, got {:?}", event),
+	}
+	assert!(matches!(events[1], KittiesEvent::KittyTransferred { kitty_id: 2, .. }));
+	assert_eq!(owner(&node, 0, block).await, Some(Bob.to_account_id()));
+	assert_eq!(owner(&node, 1, block).await, Some(Alice.to_account_id()));
+	assert_eq!(owner(&node, 2, block).await, Some(Charlie.to_account_id()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_calls_leave_kitties_untouched() {
+	let node = TestNode::start().await;
+
+	node.submit(Alice, KittiesCall::create { name: *b"kitty-01" }).await;
+	node.seal().await;
+
+	// Bob can't give away Alice's kitty
+	node.submit(Bob, KittiesCall::transfer { recipient: Bob.to_account_id(), kitty_id: 0 })
+		.await;
+	let block = node.seal().await;
+	let events = node.events(block).await;
+	assert!(events.iter().any(|event| matches!(
+		event,
+		RuntimeEvent::System(frame_system::Event::ExtrinsicFailed { .. })
+	)));
+	assert!(kitties_events(events).is_empty());
+	assert_eq!(owner(&node, 0, block).await, Some(Alice.to_account_id()));
+}
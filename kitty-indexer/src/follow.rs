@@ -0,0 +1,301 @@
+//! Following the node's chain into the [`Store`].
+//!
+//! Finalized blocks the database is missing are indexed first, in order. After that the best
+//! chain is followed as it grows, so queries see new kitties within a block. When the best
+//! chain switches to another fork, the blocks the database has of the old fork are reverted
+//! back to the common ancestor, which is never below the last finalized block.
+
+use crate::store::{GenesisKitty, Store};
+use codec::Decode;
+use futures::StreamExt;
+use jsonrpsee::{
+	core::client::{ClientT, Subscription, SubscriptionClientT},
+	rpc_params,
+	ws_client::{WsClient, WsClientBuilder},
+};
+use node_template_runtime::{
+	opaque::Header,
+	pallet_kitties::{self, KittyId},
+	BlockNumber, Hash, Runtime, RuntimeEvent,
+};
+use serde_json::Value;
+use sp_core::bytes::{from_hex, to_hex};
+use sp_runtime::traits::Header as _;
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex, MutexGuard},
+};
+
+/// How many genesis kitties are read per storage query.
+const GENESIS_BATCH: u32 = 256;
+
+pub struct Follower {
+	rpc: WsClient,
+	store: Arc<Mutex<Store>>,
+}
+
+impl Follower {
+	pub async fn connect(url: &str, store: Arc<Mutex<Store>>) -> Result<Self, String> {
+		let rpc = WsClientBuilder::default()
+			.build(url)
+			.await
+			.map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+		Ok(Follower { rpc, store })
+	}
+
+	/// Index the chain and keep following it, until the connection to the node fails.
+	pub async fn run(&self) -> Result<(), String> {
+		let genesis_hash = self.block_hash(0).await?;
+		match self.store_hash(0)? {
+			Some(hash) if hash == genesis_hash => (),
+			Some(_) => return Err("The database indexes a different chain than the node's".into()),
+			None => self.index_genesis(genesis_hash).await?,
+		}
+		self.catch_up().await?;
+
+		// Blocks produced while catching up are indexed on the first new head.
+		let mut best =
+			self.subscribe("chain_subscribeNewHeads", "chain_unsubscribeNewHeads").await?;
+		let mut finalized = self
+			.subscribe("chain_subscribeFinalizedHeads", "chain_unsubscribeFinalizedHeads")
+			.await?;
+
+		loop {
+			tokio::select! {
+				head = best.next() => {
+					self.follow(next_head(head)?).await?;
+				},
+				head = finalized.next() => {
+					let head = next_head(head)?;
+					self.follow(head.clone()).await?;
+					self.store().set_finalized(*head.number()).map_err(db_error)?;
+				},
+			}
+		}
+	}
+
+	/// Index the finalized blocks the database is missing, reverting anything indexed past
+	/// finality that has since been abandoned.
+	async fn catch_up(&self) -> Result<(), String> {
+		let finalized_hash: Hash = self
+			.rpc
+			.request("chain_getFinalizedHead", rpc_params![])
+			.await
+			.map_err(rpc_error)?;
+		let finalized = *self.header(finalized_hash).await?.number();
+
+		let (tip, tip_hash) = self.tip()?;
+		let checked = tip.min(finalized);
+		let indexed = if tip <= finalized { Some(tip_hash) } else { self.store_hash(checked)? };
+		if indexed != Some(self.block_hash(checked).await?) {
+			let number = self.store().finalized().map_err(db_error)?;
+			log::info!("Reverting blocks #{}..=#{} abandoned since the last run", number + 1, tip);
+			self.store().revert_to(number).map_err(db_error)?;
+		}
+
+		let (tip, _) = self.tip()?;
+		if tip < finalized {
+			log::info!("Catching up from #{} to finalized block #{}", tip + 1, finalized);
+		}
+		for number in tip + 1..=finalized {
+			let hash = self.block_hash(number).await?;
+			self.index_block(number, hash).await?;
+			if number % 1_000 == 0 {
+				log::info!("Indexed #{}", number);
+			}
+		}
+		self.store().set_finalized(finalized).map_err(db_error)
+	}
+
+	/// Make `head` and its ancestors the indexed chain, reverting blocks of any other fork.
+	async fn follow(&self, head: Header) -> Result<(), String> {
+		// Walk back from `head` to the newest block the database already has.
+		let head_hash = head.hash();
+		let mut branch = Vec::new();
+		let mut header = head;
+		let mut hash = header.hash();
+		while self.store_hash(*header.number())? != Some(hash) {
+			if *header.number() == 0 {
+				return Err("The node's chain doesn't share the indexed genesis block".into())
+			}
+			let parent = *header.parent_hash();
+			branch.push((*header.number(), hash));
+			header = self.header(parent).await?;
+			hash = parent;
+		}
+		if branch.is_empty() {
+			return Ok(())
+		}
+
+		let ancestor = *header.number();
+		let (tip, _) = self.tip()?;
+		if ancestor < tip {
+			let finalized = self.store().finalized().map_err(db_error)?;
+			if ancestor < finalized {
+				return Err(format!(
+					"The chain reorganized below finalized block #{} to #{}",
+					finalized, ancestor
+				));
+			}
+			log::info!("Reorg: reverting #{}..=#{} to follow {:?}", ancestor + 1, tip, head_hash);
+			self.store().revert_to(ancestor).map_err(db_error)?;
+		}
+		for (number, hash) in branch.into_iter().rev() {
+			self.index_block(number, hash).await?;
+		}
+		Ok(())
+	}
+
+	async fn index_block(&self, number: BlockNumber, hash: Hash) -> Result<(), String> {
+		let key = frame_support::storage::storage_prefix(b"System", b"Events").to_vec();
+		let records: Vec<frame_system::EventRecord<RuntimeEvent, Hash>> = self
+			.storage(key, hash)
+			.await
+			.map_err(|e| format!("Failed to read the events of block #{}: {}", number, e))?;
+		let events: Vec<_> = records
+			.into_iter()
+			.enumerate()
+			.filter_map(|(index, record)| match record.event {
+				RuntimeEvent::KittiesModule(event) => Some((index as u32, event)),
+				_ => None,
+			})
+			.collect();
+		self.store().insert_block(number, hash, &events).map_err(db_error)
+	}
+
+	/// Genesis kitties are built without events, so read them from the genesis state.
+	async fn index_genesis(&self, hash: Hash) -> Result<(), String> {
+		let next_id: KittyId = self
+			.storage(pallet_kitties::NextKittyId::<Runtime>::hashed_key().to_vec(), hash)
+			.await?;
+		log::info!("Indexing {} genesis kitties", next_id);
+
+		let mut kitties = Vec::new();
+		for start in (0..next_id).step_by(GENESIS_BATCH as usize) {
+			let ids: Vec<KittyId> = (start..next_id.min(start + GENESIS_BATCH)).collect();
+			let keys = ids.iter().flat_map(|id| {
+				[
+					pallet_kitties::Kitties::<Runtime>::hashed_key_for(id),
+					pallet_kitties::KittyOwner::<Runtime>::hashed_key_for(id),
+					pallet_kitties::KittyCollection::<Runtime>::hashed_key_for(id),
+					pallet_kitties::KittyOnSale::<Runtime>::hashed_key_for(id),
+				]
+			});
+			let mut values = self.storage_batch(keys.collect(), hash).await?.into_iter();
+			for id in ids {
+				let mut value = || values.next().expect("a value is returned per key; qed");
+				let (kitty, owner, collection_id, on_sale) = (value(), value(), value(), value());
+				let (kitty, owner) = match (kitty, owner) {
+					(Some(kitty), Some(owner)) => (kitty, owner),
+					_ => continue,
+				};
+				kitties.push(GenesisKitty {
+					id,
+					kitty: decode(&kitty)?,
+					owner: decode(&owner)?,
+					collection_id: collection_id
+						.map(|c| decode(&c))
+						.transpose()?
+						.unwrap_or_default(),
+					on_sale: on_sale.is_some(),
+				});
+			}
+		}
+		self.store().insert_genesis(hash, &kitties).map_err(db_error)
+	}
+
+	async fn subscribe(
+		&self,
+		method: &str,
+		unsubscribe: &str,
+	) -> Result<Subscription<Header>, String> {
+		self.rpc.subscribe(method, rpc_params![], unsubscribe).await.map_err(rpc_error)
+	}
+
+	async fn header(&self, hash: Hash) -> Result<Header, String> {
+		let header: Option<Header> = self
+			.rpc
+			.request("chain_getHeader", rpc_params![hash])
+			.await
+			.map_err(rpc_error)?;
+		header.ok_or_else(|| format!("The node has no header for block {:?}", hash))
+	}
+
+	async fn block_hash(&self, number: BlockNumber) -> Result<Hash, String> {
+		let hash: Option<Hash> = self
+			.rpc
+			.request("chain_getBlockHash", rpc_params![number])
+			.await
+			.map_err(rpc_error)?;
+		hash.ok_or_else(|| format!("The node has no block #{}", number))
+	}
+
+	/// Read and decode the value at the raw storage `key` in block `at`, or its default.
+	async fn storage<T: Decode + Default>(&self, key: Vec<u8>, at: Hash) -> Result<T, String> {
+		let value: Option<String> = self
+			.rpc
+			.request("state_getStorage", rpc_params![to_hex(&key, false), at])
+			.await
+			.map_err(rpc_error)?;
+		match value {
+			Some(value) => decode(&from_hex(&value).map_err(|e| format!("Invalid hex: {}", e))?),
+			None => Ok(T::default()),
+		}
+	}
+
+	/// Read the raw values at `keys` in block `at`, in the order of `keys`.
+	async fn storage_batch(
+		&self,
+		keys: Vec<Vec<u8>>,
+		at: Hash,
+	) -> Result<Vec<Option<Vec<u8>>>, String> {
+		let hex_keys: Vec<String> = keys.iter().map(|key| to_hex(key, false)).collect();
+		let change_sets: Vec<Value> = self
+			.rpc
+			.request("state_queryStorageAt", rpc_params![hex_keys.clone(), at])
+			.await
+			.map_err(rpc_error)?;
+		let mut values = HashMap::new();
+		for change_set in change_sets {
+			for change in change_set["changes"].as_array().into_iter().flatten() {
+				if let (Some(key), Some(value)) = (change[0].as_str(), change[1].as_str()) {
+					let value = from_hex(value).map_err(|e| format!("Invalid hex: {}", e))?;
+					values.insert(key.to_string(), value);
+				}
+			}
+		}
+		Ok(hex_keys.iter().map(|key| values.remove(key)).collect())
+	}
+
+	fn tip(&self) -> Result<(BlockNumber, Hash), String> {
+		let tip = self.store().tip().map_err(db_error)?;
+		Ok(tip.expect("the genesis block is indexed before anything else; qed"))
+	}
+
+	fn store_hash(&self, number: BlockNumber) -> Result<Option<Hash>, String> {
+		self.store().block_hash(number).map_err(db_error)
+	}
+
+	fn store(&self) -> MutexGuard<Store> {
+		self.store.lock().expect("the store is never used while panicking; qed")
+	}
+}
+
+fn next_head(head: Option<Result<Header, jsonrpsee::core::Error>>) -> Result<Header, String> {
+	match head {
+		Some(head) => head.map_err(rpc_error),
+		None => Err("The node closed the block subscription".into()),
+	}
+}
+
+fn decode<T: Decode>(bytes: &[u8]) -> Result<T, String> {
+	T::decode(&mut &bytes[..]).map_err(|e| format!("Undecodable storage: {}", e))
+}
+
+fn rpc_error(error: jsonrpsee::core::Error) -> String {
+	format!("RPC error: {}", error)
+}
+
+pub fn db_error(error: rusqlite::Error) -> String {
+	format!("Database error: {}", error)
+}
@@ -0,0 +1,127 @@
+//! The HTTP endpoint answering queries from the [`Store`] as JSON.
+//!
+//! - `GET /status`: the indexed and finalized block numbers.
+//! - `GET /kitties/{id}`: a kitty with its owner, DNA, listing, parents and children.
+//! - `GET /kitties/{id}/history`: every change of hands of a kitty.
+//! - `GET /kitties/{id}/ancestors?depth=8`: the ancestors of a kitty, nearest first.
+//! - `GET /owners/{address}/kitties`: the kitties an account owns.
+//! - `GET /sales?limit=50`: the latest sales, newest first.
+
+use crate::{follow::db_error, store::Store};
+use hyper::{
+	header::CONTENT_TYPE,
+	service::{make_service_fn, service_fn},
+	Body, Method, Request, Response, Server, StatusCode, Uri,
+};
+use node_template_runtime::{pallet_kitties::KittyId, AccountId};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sp_core::crypto::Ss58Codec;
+use std::{
+	convert::Infallible,
+	net::SocketAddr,
+	sync::{Arc, Mutex},
+};
+
+/// The most generations of ancestors returned.
+const MAX_DEPTH: u32 = 64;
+
+/// The most sales returned.
+const MAX_SALES: u32 = 1_000;
+
+/// Serve queries on `addr` until the server fails.
+pub async fn serve(addr: SocketAddr, store: Arc<Mutex<Store>>) -> Result<(), String> {
+	let make_service = make_service_fn(move |_| {
+		let store = store.clone();
+		async move {
+			Ok::<_, Infallible>(service_fn(move |request| {
+				let store = store.clone();
+				async move { Ok::<_, Infallible>(respond(&store, &request)) }
+			}))
+		}
+	});
+	let server = Server::try_bind(&addr)
+		.map_err(|e| format!("Failed to listen on {}: {}", addr, e))?
+		.serve(make_service);
+	log::info!("Serving kitty queries on http://{}", addr);
+	server.await.map_err(|e| format!("HTTP server failed: {}", e))
+}
+
+fn respond(store: &Mutex<Store>, request: &Request<Body>) -> Response<Body> {
+	if request.method() != Method::GET {
+		return reply(StatusCode::METHOD_NOT_ALLOWED, "Only GET is supported")
+	}
+	let store = store.lock().expect("the store is never used while panicking; qed");
+	match route(&store, request.uri()) {
+		Ok(Some(body)) => Response::builder()
+			.header(CONTENT_TYPE, "application/json")
+			.body(Body::from(body.to_string()))
+			.expect("the response is valid; qed"),
+		Ok(None) => reply(StatusCode::NOT_FOUND, "Not found"),
+		Err((status, error)) => reply(status, &error),
+	}
+}
+
+fn route(store: &Store, uri: &Uri) -> Result<Option<Value>, (StatusCode, String)> {
+	let path: Vec<&str> = uri.path().split('/').filter(|segment| !segment.is_empty()).collect();
+	let found = match path[..] {
+		["status"] => store.status().map_err(internal)?.map(to_json),
+		["kitties", id] => store.kitty(kitty_id(id)?).map_err(internal)?.map(to_json),
+		["kitties", id, "history"] =>
+			Some(to_json(store.history(kitty_id(id)?).map_err(internal)?)),
+		["kitties", id, "ancestors"] => {
+			let depth = param(uri, "depth", 8, MAX_DEPTH)?;
+			Some(to_json(store.ancestors(kitty_id(id)?, depth).map_err(internal)?))
+		},
+		["owners", address, "kitties"] => {
+			let owner = AccountId::from_ss58check(address)
+				.map_err(|e| bad_request(format!("Invalid SS58 address: {:?}", e)))?;
+			Some(to_json(store.owned_by(&owner.to_ss58check()).map_err(internal)?))
+		},
+		["sales"] =>
+			Some(to_json(store.sales(param(uri, "limit", 50, MAX_SALES)?).map_err(internal)?)),
+		_ => None,
+	};
+	Ok(found)
+}
+
+fn kitty_id(id: &str) -> Result<KittyId, (StatusCode, String)> {
+	id.parse().map_err(|_| bad_request(format!("Invalid kitty id `{}`", id)))
+}
+
+/// The query parameter `name`, or `default`, capped at `max`.
+fn param(uri: &Uri, name: &str, default: u32, max: u32) -> Result<u32, (StatusCode, String)> {
+	let value = uri
+		.query()
+		.unwrap_or_default()
+		.split('&')
+		.find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='));
+	match value {
+		Some(value) => value
+			.parse::<u32>()
+			.map(|value| value.min(max))
+			.map_err(|_| bad_request(format!("Invalid {} `{}`", name, value))),
+		None => Ok(default),
+	}
+}
+
+fn to_json<T: Serialize>(value: T) -> Value {
+	serde_json::to_value(value).expect("query results encode; qed")
+}
+
+fn reply(status: StatusCode, error: &str) -> Response<Body> {
+	Response::builder()
+		.status(status)
+		.header(CONTENT_TYPE, "application/json")
+		.body(Body::from(json!({ "error": error }).to_string()))
+		.expect("the response is valid; qed")
+}
+
+fn bad_request(error: String) -> (StatusCode, String) {
+	(StatusCode::BAD_REQUEST, error)
+}
+
+fn internal(error: rusqlite::Error) -> (StatusCode, String) {
+	log::error!("{}", db_error(error));
+	(StatusCode::INTERNAL_SERVER_ERROR, "Database error".into())
+}
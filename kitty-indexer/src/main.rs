@@ -0,0 +1,50 @@
+//! Indexes the kitties of a running node into a local SQLite database, and answers queries
+//! about them over HTTP.
+//!
+//! The database keeps every kitty with its lineage, the history of who owned it and the
+//! listings and sales of the market. It is resumed from the last indexed block when the
+//! indexer restarts, and the indexer exits when it loses the node, to be restarted by whatever
+//! supervises it.
+
+mod follow;
+mod http;
+mod store;
+
+use clap::Parser;
+use follow::{db_error, Follower};
+use std::{
+	net::SocketAddr,
+	path::PathBuf,
+	sync::{Arc, Mutex},
+};
+use store::Store;
+
+#[derive(Debug, clap::Parser)]
+struct Cli {
+	/// WebSocket RPC endpoint of the node to index.
+	#[arg(long, default_value = "ws://127.0.0.1:9944")]
+	url: String,
+
+	/// The SQLite database to write, created if it doesn't exist.
+	#[arg(long, default_value = "kitties.db")]
+	database: PathBuf,
+
+	/// The address to serve queries on.
+	#[arg(long, default_value = "127.0.0.1:9955")]
+	listen: SocketAddr,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+	env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+	let cli = Cli::parse();
+
+	let store = Store::open(&cli.database).map_err(db_error)?;
+	let store = Arc::new(Mutex::new(store));
+	let follower = Follower::connect(&cli.url, store.clone()).await?;
+
+	tokio::select! {
+		result = follower.run() => result,
+		result = http::serve(cli.listen, store) => result,
+	}
+}
@@ -0,0 +1,753 @@
+//! The SQLite database of indexed kitties.
+//!
+//! Every row records the block it came from and rows are never updated, so reverting blocks
+//! abandoned by a reorg is a matter of deleting the rows above the common ancestor. A kitty's
+//! current owner, DNA and listing are the latest entries of their histories.
+
+use node_template_runtime::{
+	pallet_kitties::{self, CollectionId, Kitty, KittyId},
+	AccountId, Balance, BlockNumber, Hash, Runtime,
+};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::Serialize;
+use sp_core::{bytes::to_hex, crypto::Ss58Codec};
+use std::{collections::HashSet, path::Path};
+
+/// The most ancestors returned for one kitty, however deep the lineage is followed.
+pub const MAX_ANCESTORS: usize = 1_000;
+
+const SCHEMA: &str = "
+	CREATE TABLE IF NOT EXISTS blocks (
+		number INTEGER PRIMARY KEY,
+		hash BLOB NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS meta (
+		key TEXT PRIMARY KEY,
+		value INTEGER NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS kitties (
+		id INTEGER PRIMARY KEY,
+		name TEXT NOT NULL,
+		collection_id INTEGER NOT NULL,
+		parent_1 INTEGER,
+		parent_2 INTEGER,
+		block INTEGER NOT NULL
+	);
+	CREATE INDEX IF NOT EXISTS kitties_parent_1 ON kitties (parent_1);
+	CREATE INDEX IF NOT EXISTS kitties_parent_2 ON kitties (parent_2);
+	CREATE TABLE IF NOT EXISTS dna (
+		kitty_id INTEGER NOT NULL,
+		block INTEGER NOT NULL,
+		event_index INTEGER NOT NULL,
+		dna TEXT NOT NULL
+	);
+	CREATE INDEX IF NOT EXISTS dna_kitty ON dna (kitty_id, block, event_index);
+	CREATE TABLE IF NOT EXISTS ownership (
+		kitty_id INTEGER NOT NULL,
+		block INTEGER NOT NULL,
+		event_index INTEGER NOT NULL,
+		owner TEXT,
+		reason TEXT NOT NULL
+	);
+	CREATE INDEX IF NOT EXISTS ownership_kitty ON ownership (kitty_id, block, event_index);
+	CREATE INDEX IF NOT EXISTS ownership_owner ON ownership (owner);
+	CREATE TABLE IF NOT EXISTS market (
+		kitty_id INTEGER NOT NULL,
+		block INTEGER NOT NULL,
+		event_index INTEGER NOT NULL,
+		kind TEXT NOT NULL,
+		seller TEXT,
+		buyer TEXT,
+		price TEXT
+	);
+	CREATE INDEX IF NOT EXISTS market_kitty ON market (kitty_id, block, event_index);
+	CREATE TABLE IF NOT EXISTS swaps (
+		id INTEGER PRIMARY KEY,
+		proposer TEXT NOT NULL,
+		offered INTEGER NOT NULL,
+		wanted INTEGER NOT NULL,
+		block INTEGER NOT NULL
+	);
+";
+
+/// The tables whose rows come from a block, in its `block` column.
+const INDEXED_TABLES: [&str; 5] = ["kitties", "dna", "ownership", "market", "swaps"];
+
+/// A kitty present in the genesis block, which has no events.
+pub struct GenesisKitty {
+	pub id: KittyId,
+	pub kitty: Kitty,
+	pub owner: AccountId,
+	pub collection_id: CollectionId,
+	pub on_sale: bool,
+}
+
+/// How far the database is indexed.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+	pub tip: BlockNumber,
+	pub tip_hash: Hash,
+	pub finalized: BlockNumber,
+}
+
+/// A kitty as it stands at the indexed tip.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KittyRecord {
+	pub id: KittyId,
+	pub name: String,
+	pub dna: String,
+	/// `None` once burned, or while fractionalized.
+	pub owner: Option<String>,
+	pub collection_id: CollectionId,
+	pub parents: Option<(KittyId, KittyId)>,
+	pub children: Vec<KittyId>,
+	pub on_sale: bool,
+	/// The asking price while listed. Listings made at genesis have no known price.
+	pub price: Option<String>,
+	/// The block the kitty was created in.
+	pub block: BlockNumber,
+}
+
+/// A change of hands of a kitty.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ownership {
+	pub block: BlockNumber,
+	pub event_index: u32,
+	pub owner: Option<String>,
+	pub reason: String,
+}
+
+/// A kitty bought from its seller.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sale {
+	pub kitty_id: KittyId,
+	pub block: BlockNumber,
+	pub seller: String,
+	pub buyer: String,
+	pub price: String,
+}
+
+/// An ancestor of a kitty, `generation` generations back.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ancestor {
+	pub id: KittyId,
+	pub generation: u32,
+	pub parents: Option<(KittyId, KittyId)>,
+}
+
+pub struct Store {
+	conn: Connection,
+}
+
+impl Store {
+	/// Open the database at `path`, creating it if needed.
+	pub fn open(path: &Path) -> rusqlite::Result<Self> {
+		Self::init(Connection::open(path)?)
+	}
+
+	#[cfg(test)]
+	pub fn in_memory() -> rusqlite::Result<Self> {
+		Self::init(Connection::open_in_memory()?)
+	}
+
+	fn init(conn: Connection) -> rusqlite::Result<Self> {
+		conn.execute_batch(SCHEMA)?;
+		Ok(Store { conn })
+	}
+
+	/// The last indexed block, if any.
+	pub fn tip(&self) -> rusqlite::Result<Option<(BlockNumber, Hash)>> {
+		self.conn
+			.query_row("SELECT number, hash FROM blocks ORDER BY number DESC LIMIT 1", [], |row| {
+				Ok((row.get(0)?, hash(row.get(1)?)))
+			})
+			.optional()
+	}
+
+	/// The hash of indexed block `number`, if it is indexed.
+	pub fn block_hash(&self, number: BlockNumber) -> rusqlite::Result<Option<Hash>> {
+		self.conn
+			.query_row("SELECT hash FROM blocks WHERE number = ?1", [number], |row| {
+				Ok(hash(row.get(0)?))
+			})
+			.optional()
+	}
+
+	/// The highest indexed block known to be finalized.
+	pub fn finalized(&self) -> rusqlite::Result<BlockNumber> {
+		let finalized = self
+			.conn
+			.query_row("SELECT value FROM meta WHERE key = 'finalized'", [], |row| row.get(0))
+			.optional()?;
+		Ok(finalized.unwrap_or(0))
+	}
+
+	pub fn set_finalized(&self, number: BlockNumber) -> rusqlite::Result<()> {
+		self.conn.execute(
+			"INSERT INTO meta (key, value) VALUES ('finalized', ?1)
+				ON CONFLICT (key) DO UPDATE SET value = MAX(value, excluded.value)",
+			[number],
+		)?;
+		Ok(())
+	}
+
+	pub fn status(&self) -> rusqlite::Result<Option<Status>> {
+		let finalized = self.finalized()?;
+		Ok(self.tip()?.map(|(tip, tip_hash)| Status { tip, tip_hash, finalized }))
+	}
+
+	/// Index the genesis block `hash` with the kitties it was built with.
+	pub fn insert_genesis(&mut self, hash: Hash, kitties: &[GenesisKitty]) -> rusqlite::Result<()> {
+		let tx = self.conn.transaction()?;
+		tx.execute("INSERT INTO blocks (number, hash) VALUES (0, ?1)", [hash.as_bytes()])?;
+		for genesis in kitties {
+			let index = genesis.id;
+			insert_kitty(&tx, 0, index, genesis.id, &genesis.kitty, genesis.collection_id, None)?;
+			own(&tx, 0, index, genesis.id, Some(&genesis.owner), "genesis")?;
+			if genesis.on_sale {
+				trade(&tx, 0, index, genesis.id, "listed", Some(&genesis.owner), None, None)?;
+			}
+		}
+		tx.commit()
+	}
+
+	/// Index block `number` with the kitty events it emitted, each with its index in the block.
+	pub fn insert_block(
+		&mut self,
+		number: BlockNumber,
+		hash: Hash,
+		events: &[(u32, pallet_kitties::Event<Runtime>)],
+	) -> rusqlite::Result<()> {
+		let tx = self.conn.transaction()?;
+		tx.execute(
+			"INSERT INTO blocks (number, hash) VALUES (?1, ?2)",
+			params![number, hash.as_bytes()],
+		)?;
+		for (index, event) in events {
+			apply(&tx, number, *index, event)?;
+		}
+		tx.commit()
+	}
+
+	/// Forget every block above `number`, and everything they did.
+	pub fn revert_to(&mut self, number: BlockNumber) -> rusqlite::Result<()> {
+		let tx = self.conn.transaction()?;
+		for table in INDEXED_TABLES {
+			tx.execute(&format!("DELETE FROM {} WHERE block > ?1", table), [number])?;
+		}
+		tx.execute("DELETE FROM blocks WHERE number > ?1", [number])?;
+		tx.commit()
+	}
+
+	pub fn kitty(&self, id: KittyId) -> rusqlite::Result<Option<KittyRecord>> {
+		let kitty = self
+			.conn
+			.query_row(
+				"SELECT name, collection_id, parent_1, parent_2, block FROM kitties WHERE id = ?1",
+				[id],
+				|row| {
+					let parents = match (row.get(2)?, row.get(3)?) {
+						(Some(parent_1), Some(parent_2)) => Some((parent_1, parent_2)),
+						_ => None,
+					};
+					Ok((row.get::<_, String>(0)?, row.get(1)?, parents, row.get(4)?))
+				},
+			)
+			.optional()?;
+		let (name, collection_id, parents, block) = match kitty {
+			Some(kitty) => kitty,
+			None => return Ok(None),
+		};
+
+		let dna = self.conn.query_row(
+			"SELECT dna FROM dna WHERE kitty_id = ?1
+				ORDER BY block DESC, event_index DESC LIMIT 1",
+			[id],
+			|row| row.get(0),
+		)?;
+		let (owner, owned_since) = self.conn.query_row(
+			"SELECT owner, block, event_index FROM ownership WHERE kitty_id = ?1
+				ORDER BY block DESC, event_index DESC LIMIT 1",
+			[id],
+			|row| Ok((row.get::<_, Option<String>>(0)?, (row.get(1)?, row.get(2)?))),
+		)?;
+		// A listing lapses when the kitty changes hands. Genesis listings share their position
+		// with the genesis ownership, so a listing at the ownership's position still counts.
+		let listing = self
+			.conn
+			.query_row(
+				"SELECT kind, price, block, event_index FROM market WHERE kitty_id = ?1
+					ORDER BY block DESC, event_index DESC LIMIT 1",
+				[id],
+				|row| {
+					let listed_at: (BlockNumber, u32) = (row.get(2)?, row.get(3)?);
+					Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, listed_at))
+				},
+			)
+			.optional()?
+			.filter(|(kind, _, listed_at)| kind == "listed" && *listed_at >= owned_since);
+		let mut children = self
+			.conn
+			.prepare("SELECT id FROM kitties WHERE parent_1 = ?1 OR parent_2 = ?1 ORDER BY id")?;
+		let children = children.query_map([id], |row| row.get(0))?.collect::<Result<_, _>>()?;
+
+		Ok(Some(KittyRecord {
+			id,
+			name,
+			dna,
+			owner,
+			collection_id,
+			parents,
+			children,
+			on_sale: listing.is_some(),
+			price: listing.and_then(|(_, price, _)| price),
+			block,
+		}))
+	}
+
+	/// Every change of hands of kitty `id`, oldest first.
+	pub fn history(&self, id: KittyId) -> rusqlite::Result<Vec<Ownership>> {
+		let mut history = self.conn.prepare(
+			"SELECT block, event_index, owner, reason FROM ownership WHERE kitty_id = ?1
+				ORDER BY block, event_index",
+		)?;
+		let history = history.query_map([id], |row| {
+			Ok(Ownership {
+				block: row.get(0)?,
+				event_index: row.get(1)?,
+				owner: row.get(2)?,
+				reason: row.get(3)?,
+			})
+		})?;
+		history.collect()
+	}
+
+	/// The kitties currently owned by `owner`, an SS58 address.
+	pub fn owned_by(&self, owner: &str) -> rusqlite::Result<Vec<KittyId>> {
+		let mut owned = self.conn.prepare(
+			"SELECT kitty_id FROM ownership AS o WHERE owner = ?1 AND NOT EXISTS (
+				SELECT 1 FROM ownership AS later WHERE later.kitty_id = o.kitty_id AND
+					(later.block, later.event_index) > (o.block, o.event_index)
+			) ORDER BY kitty_id",
+		)?;
+		let owned = owned.query_map([owner], |row| row.get(0))?;
+		owned.collect()
+	}
+
+	/// The most recent sales, newest first.
+	pub fn sales(&self, limit: u32) -> rusqlite::Result<Vec<Sale>> {
+		let mut sales = self.conn.prepare(
+			"SELECT kitty_id, block, seller, buyer, price FROM market WHERE kind = 'sold'
+				ORDER BY block DESC, event_index DESC LIMIT ?1",
+		)?;
+		let sales = sales.query_map([limit], |row| {
+			Ok(Sale {
+				kitty_id: row.get(0)?,
+				block: row.get(1)?,
+				seller: row.get(2)?,
+				buyer: row.get(3)?,
+				price: row.get(4)?,
+			})
+		})?;
+		sales.collect()
+	}
+
+	/// The ancestors of kitty `id` up to `depth` generations back, nearest first. Ancestors
+	/// reached through several lines are listed once, and at most [`MAX_ANCESTORS`] are listed.
+	pub fn ancestors(&self, id: KittyId, depth: u32) -> rusqlite::Result<Vec<Ancestor>> {
+		let mut ancestors = Vec::new();
+		let mut seen = HashSet::new();
+		let mut generation = vec![id];
+		for distance in 1..=depth {
+			let mut next = Vec::new();
+			for kitty_id in generation {
+				for parent in self.parents(kitty_id)?.into_iter().flat_map(|(p1, p2)| [p1, p2]) {
+					if ancestors.len() == MAX_ANCESTORS {
+						return Ok(ancestors)
+					}
+					if seen.insert(parent) {
+						let parents = self.parents(parent)?;
+						ancestors.push(Ancestor { id: parent, generation: distance, parents });
+						next.push(parent);
+					}
+				}
+			}
+			generation = next;
+		}
+		Ok(ancestors)
+	}
+
+	fn parents(&self, id: KittyId) -> rusqlite::Result<Option<(KittyId, KittyId)>> {
+		let parents = self
+			.conn
+			.query_row("SELECT parent_1, parent_2 FROM kitties WHERE id = ?1", [id], |row| {
+				Ok((row.get::<_, Option<KittyId>>(0)?, row.get::<_, Option<KittyId>>(1)?))
+			})
+			.optional()?;
+		Ok(match parents {
+			Some((Some(parent_1), Some(parent_2))) => Some((parent_1, parent_2)),
+			_ => None,
+		})
+	}
+}
+
+/// Record what `event`, the `index`th event of block `block`, did.
+fn apply(
+	tx: &Transaction,
+	block: BlockNumber,
+	index: u32,
+	event: &pallet_kitties::Event<Runtime>,
+) -> rusqlite::Result<()> {
+	use pallet_kitties::Event::*;
+	match event {
+		KittyCreated { who, collection_id, kitty_id, kitty } => {
+			insert_kitty(tx, block, index, *kitty_id, kitty, *collection_id, None)?;
+			own(tx, block, index, *kitty_id, Some(who), "created")
+		},
+		KittyBred { who, kitty_id, kitty, parents } => {
+			// Bred kitties join their parents' collection.
+			let collection_id = tx
+				.query_row("SELECT collection_id FROM kitties WHERE id = ?1", [parents.0], |row| {
+					row.get(0)
+				})
+				.optional()?
+				.unwrap_or(pallet_kitties::DEFAULT_COLLECTION);
+			insert_kitty(tx, block, index, *kitty_id, kitty, collection_id, Some(*parents))?;
+			own(tx, block, index, *kitty_id, Some(who), "bred")
+		},
+		KittyTransferred { recipient, kitty_id, .. } =>
+			own(tx, block, index, *kitty_id, Some(recipient), "transferred"),
+		KittyOnSale { who, kitty_id, price } =>
+			trade(tx, block, index, *kitty_id, "listed", Some(who), None, Some(*price)),
+		KittyBought { who, seller, kitty_id, price } => {
+			trade(tx, block, index, *kitty_id, "sold", Some(seller), Some(who), Some(*price))?;
+			own(tx, block, index, *kitty_id, Some(who), "bought")
+		},
+		KittyDelisted { kitty_id } =>
+			trade(tx, block, index, *kitty_id, "delisted", None, None, None),
+		SwapProposed { who, swap_id, offered, wanted, .. } => {
+			tx.execute(
+				"INSERT INTO swaps (id, proposer, offered, wanted, block) VALUES (?1, ?2, ?3, ?4, ?5)",
+				params![swap_id, who.to_ss58check(), offered, wanted, block],
+			)?;
+			Ok(())
+		},
+		SwapAccepted { who, proposer, swap_id } => {
+			let swap: Option<(KittyId, KittyId)> = tx
+				.query_row("SELECT offered, wanted FROM swaps WHERE id = ?1", [swap_id], |row| {
+					Ok((row.get(0)?, row.get(1)?))
+				})
+				.optional()?;
+			match swap {
+				Some((offered, wanted)) => {
+					own(tx, block, index, offered, Some(who), "swapped")?;
+					own(tx, block, index, wanted, Some(proposer), "swapped")
+				},
+				None => {
+					log::warn!("Swap {} accepted in block #{} was never proposed", swap_id, block);
+					Ok(())
+				},
+			}
+		},
+		KittyForceTransferred { kitty_id, to, .. } =>
+			own(tx, block, index, *kitty_id, Some(to), "force_transferred"),
+		KittyBurned { kitty_id, .. } => own(tx, block, index, *kitty_id, None, "burned"),
+		KittyDnaSet { kitty_id, dna } => {
+			tx.execute(
+				"INSERT INTO dna (kitty_id, block, event_index, dna) VALUES (?1, ?2, ?3, ?4)",
+				params![kitty_id, block, index, to_hex(dna, false)],
+			)?;
+			Ok(())
+		},
+		KittyFractionalized { kitty_id, .. } =>
+			own(tx, block, index, *kitty_id, None, "fractionalized"),
+		KittyRedeemed { who, kitty_id } => own(tx, block, index, *kitty_id, Some(who), "redeemed"),
+		_ => Ok(()),
+	}
+}
+
+fn insert_kitty(
+	tx: &Transaction,
+	block: BlockNumber,
+	index: u32,
+	id: KittyId,
+	kitty: &Kitty,
+	collection_id: CollectionId,
+	parents: Option<(KittyId, KittyId)>,
+) -> rusqlite::Result<()> {
+	let name = String::from_utf8_lossy(&kitty.name).trim_end_matches('\0').to_string();
+	tx.execute(
+		"INSERT INTO kitties (id, name, collection_id, parent_1, parent_2, block)
+			VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+		params![id, name, collection_id, parents.map(|p| p.0), parents.map(|p| p.1), block],
+	)?;
+	tx.execute(
+		"INSERT INTO dna (kitty_id, block, event_index, dna) VALUES (?1, ?2, ?3, ?4)",
+		params![id, block, index, to_hex(&kitty.dna, false)],
+	)?;
+	Ok(())
+}
+
+fn own(
+	tx: &Transaction,
+	block: BlockNumber,
+	index: u32,
+	kitty_id: KittyId,
+	owner: Option<&AccountId>,
+	reason: &str,
+) -> rusqlite::Result<()> {
+	tx.execute(
+		"INSERT INTO ownership (kitty_id, block, event_index, owner, reason)
+			VALUES (?1, ?2, ?3, ?4, ?5)",
+		params![kitty_id, block, index, owner.map(|owner| owner.to_ss58check()), reason],
+	)?;
+	Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn trade(
+	tx: &Transaction,
+	block: BlockNumber,
+	index: u32,
+	kitty_id: KittyId,
+	kind: &str,
+	seller: Option<&AccountId>,
+	buyer: Option<&AccountId>,
+	price: Option<Balance>,
+) -> rusqlite::Result<()> {
+	tx.execute(
+		"INSERT INTO market (kitty_id, block, event_index, kind, seller, buyer, price)
+			VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+		params![
+			kitty_id,
+			block,
+			index,
+			kind,
+			seller.map(|seller| seller.to_ss58check()),
+			buyer.map(|buyer| buyer.to_ss58check()),
+			// Balances overflow SQLite integers.
+			price.map(|price| price.to_string()),
+		],
+	)?;
+	Ok(())
+}
+
+fn hash(bytes: Vec<u8>) -> Hash {
+	Hash::from_slice(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pallet_kitties::Event;
+
+	fn account(seed: u8) -> AccountId {
+		AccountId::new([seed; 32])
+	}
+
+	fn kitty(seed: u8) -> Kitty {
+		Kitty { dna: [seed; 16], name: *b"kitty\0\0\0" }
+	}
+
+	/// A store with kitty 0 owned by account 1 from genesis.
+	fn store() -> Store {
+		let mut store = Store::in_memory().unwrap();
+		let genesis = GenesisKitty {
+			id: 0,
+			kitty: kitty(0),
+			owner: account(1),
+			collection_id: pallet_kitties::DEFAULT_COLLECTION,
+			on_sale: false,
+		};
+		store.insert_genesis(Hash::repeat_byte(0), &[genesis]).unwrap();
+		store
+	}
+
+	fn created(who: u8, kitty_id: KittyId) -> Event<Runtime> {
+		Event::KittyCreated {
+			who: account(who),
+			collection_id: pallet_kitties::DEFAULT_COLLECTION,
+			kitty_id,
+			kitty: kitty(kitty_id as u8),
+		}
+	}
+
+	#[test]
+	fn kitties_are_indexed_with_owners_sales_and_lineage() {
+		let mut store = store();
+		store
+			.insert_block(
+				1,
+				Hash::repeat_byte(1),
+				&[
+					(1, created(1, 1)),
+					(
+						2,
+						Event::KittyBred {
+							who: account(1),
+							kitty_id: 2,
+							kitty: kitty(2),
+							parents: (0, 1),
+						},
+					),
+				],
+			)
+			.unwrap();
+		store
+			.insert_block(
+				2,
+				Hash::repeat_byte(2),
+				&[
+					(1, Event::KittyOnSale { who: account(1), kitty_id: 2, price: 500 }),
+					(3, Event::KittyOnSale { who: account(1), kitty_id: 1, price: 700 }),
+				],
+			)
+			.unwrap();
+		store
+			.insert_block(
+				3,
+				Hash::repeat_byte(3),
+				&[(
+					1,
+					Event::KittyBought {
+						who: account(2),
+						seller: account(1),
+						kitty_id: 2,
+						price: 500,
+					},
+				)],
+			)
+			.unwrap();
+
+		let bred = store.kitty(2).unwrap().unwrap();
+		assert_eq!(bred.owner, Some(account(2).to_ss58check()));
+		assert_eq!(bred.parents, Some((0, 1)));
+		assert_eq!(bred.dna, to_hex(&[2; 16], false));
+		assert_eq!(bred.name, "kitty");
+		assert!(!bred.on_sale);
+		let listed = store.kitty(1).unwrap().unwrap();
+		assert!(listed.on_sale);
+		assert_eq!(listed.price.as_deref(), Some("700"));
+		assert_eq!(listed.children, vec![2]);
+
+		assert_eq!(store.owned_by(&account(1).to_ss58check()).unwrap(), vec![0, 1]);
+		assert_eq!(store.owned_by(&account(2).to_ss58check()).unwrap(), vec![2]);
+		let reasons: Vec<String> =
+			store.history(2).unwrap().into_iter().map(|entry| entry.reason).collect();
+		assert_eq!(reasons, ["bred", "bought"]);
+		let sales = store.sales(10).unwrap();
+		assert_eq!(sales.len(), 1);
+		assert_eq!((sales[0].kitty_id, sales[0].price.as_str()), (2, "500"));
+		let ancestors: Vec<KittyId> =
+			store.ancestors(2, 8).unwrap().into_iter().map(|ancestor| ancestor.id).collect();
+		assert_eq!(ancestors, [0, 1]);
+	}
+
+	#[test]
+	fn reverted_blocks_leave_no_trace() {
+		let mut store = store();
+		store.insert_block(1, Hash::repeat_byte(1), &[(1, created(1, 1))]).unwrap();
+		store
+			.insert_block(
+				2,
+				Hash::repeat_byte(2),
+				&[
+					(
+						1,
+						Event::KittyTransferred {
+							who: account(1),
+							recipient: account(2),
+							kitty_id: 0,
+						},
+					),
+					(2, created(2, 2)),
+				],
+			)
+			.unwrap();
+
+		store.revert_to(1).unwrap();
+
+		assert_eq!(store.tip().unwrap(), Some((1, Hash::repeat_byte(1))));
+		assert!(store.kitty(2).unwrap().is_none());
+		assert_eq!(store.owned_by(&account(1).to_ss58check()).unwrap(), vec![0, 1]);
+		assert!(store.owned_by(&account(2).to_ss58check()).unwrap().is_empty());
+
+		// The replacement block indexes cleanly over the reverted one.
+		store.insert_block(2, Hash::repeat_byte(4), &[(1, created(2, 2))]).unwrap();
+		assert_eq!(store.block_hash(2).unwrap(), Some(Hash::repeat_byte(4)));
+		assert_eq!(store.kitty(2).unwrap().unwrap().owner, Some(account(2).to_ss58check()));
+	}
+
+	#[test]
+	fn accepted_swaps_exchange_both_kitties() {
+		let mut store = store();
+		store
+			.insert_block(
+				1,
+				Hash::repeat_byte(1),
+				&[
+					(1, created(2, 1)),
+					(
+						2,
+						Event::SwapProposed {
+							who: account(1),
+							swap_id: 0,
+							offered: 0,
+							wanted: 1,
+							top_up: None,
+						},
+					),
+				],
+			)
+			.unwrap();
+		store
+			.insert_block(
+				2,
+				Hash::repeat_byte(2),
+				&[(1, Event::SwapAccepted { who: account(2), proposer: account(1), swap_id: 0 })],
+			)
+			.unwrap();
+
+		assert_eq!(store.owned_by(&account(1).to_ss58check()).unwrap(), vec![1]);
+		assert_eq!(store.owned_by(&account(2).to_ss58check()).unwrap(), vec![0]);
+	}
+
+	#[test]
+	fn genesis_listings_stand_until_the_kitty_changes_hands() {
+		let mut store = Store::in_memory().unwrap();
+		let genesis = GenesisKitty {
+			id: 0,
+			kitty: kitty(0),
+			owner: account(1),
+			collection_id: pallet_kitties::DEFAULT_COLLECTION,
+			on_sale: true,
+		};
+		store.insert_genesis(Hash::repeat_byte(0), &[genesis]).unwrap();
+		assert!(store.kitty(0).unwrap().unwrap().on_sale);
+
+		store
+			.insert_block(
+				1,
+				Hash::repeat_byte(1),
+				&[(
+					1,
+					Event::KittyTransferred { who: account(1), recipient: account(2), kitty_id: 0 },
+				)],
+			)
+			.unwrap();
+		assert!(!store.kitty(0).unwrap().unwrap().on_sale);
+	}
+
+	#[test]
+	fn finality_only_moves_forward() {
+		let store = store();
+		store.set_finalized(5).unwrap();
+		store.set_finalized(3).unwrap();
+		assert_eq!(store.finalized().unwrap(), 5);
+	}
+}
@@ -0,0 +1,290 @@
+//! Deterministic SVG avatars rendered from kitty DNA.
+//!
+//! Every client that shows a kitty should render it with this crate, so that the same DNA
+//! always looks the same. Rendering only uses integer arithmetic and fixed formatting, and
+//! its output is pinned by the golden files under `tests/golden`.
+//!
+//! The DNA bytes are read as follows:
+//!
+//! | bytes  | trait                                   |
+//! |--------|-----------------------------------------|
+//! | 0, 1   | body hue                                |
+//! | 2      | body saturation                         |
+//! | 3      | pattern kind and hue offset             |
+//! | 4      | eye shape                               |
+//! | 5      | eye hue                                 |
+//! | 6      | ear shape                               |
+//! | 7      | mouth                                   |
+//! | 8      | background hue                          |
+//! | 9..16  | pattern placement                       |
+
+use std::fmt::Write;
+
+use pallet_kitties::Kitty;
+
+/// The width and height of rendered avatars, in SVG user units.
+pub const SIZE: u32 = 256;
+
+/// A colour in HSL, with saturation and lightness in percent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Hsl {
+	hue: u32,
+	saturation: u32,
+	lightness: u32,
+}
+
+impl Hsl {
+	fn shifted(self, degrees: u32, lightness: u32) -> Self {
+		Hsl { hue: (self.hue + degrees) % 360, lightness, ..self }
+	}
+}
+
+impl std::fmt::Display for Hsl {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "hsl({},{}%,{}%)", self.hue, self.saturation, self.lightness)
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pattern {
+	Plain,
+	Stripes,
+	Spots,
+	Patches,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Eyes {
+	Round,
+	Almond,
+	Sleepy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Ears {
+	Pointed,
+	Round,
+	Folded,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mouth {
+	Smile,
+	Neutral,
+	Open,
+}
+
+/// The traits decoded from a kitty's DNA.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Traits {
+	body: Hsl,
+	pattern: Pattern,
+	pattern_color: Hsl,
+	eyes: Eyes,
+	eye_color: Hsl,
+	ears: Ears,
+	mouth: Mouth,
+	background: Hsl,
+	placement: [u8; 7],
+}
+
+impl Traits {
+	fn decode(dna: &[u8; 16]) -> Self {
+		let body = Hsl {
+			hue: u32::from(u16::from_be_bytes([dna[0], dna[1]])) % 360,
+			saturation: 35 + u32::from(dna[2]) % 46,
+			lightness: 62,
+		};
+		let pattern = match dna[3] % 4 {
+			0 => Pattern::Plain,
+			1 => Pattern::Stripes,
+			2 => Pattern::Spots,
+			_ => Pattern::Patches,
+		};
+		let eyes = match dna[4] % 3 {
+			0 => Eyes::Round,
+			1 => Eyes::Almond,
+			_ => Eyes::Sleepy,
+		};
+		let ears = match dna[6] % 3 {
+			0 => Ears::Pointed,
+			1 => Ears::Round,
+			_ => Ears::Folded,
+		};
+		let mouth = match dna[7] % 3 {
+			0 => Mouth::Smile,
+			1 => Mouth::Neutral,
+			_ => Mouth::Open,
+		};
+		let mut placement = [0u8; 7];
+		placement.copy_from_slice(&dna[9..16]);
+
+		Traits {
+			body,
+			pattern,
+			pattern_color: body.shifted(u32::from(dna[3] / 4) * 360 / 64, 38),
+			eyes,
+			eye_color: Hsl { hue: u32::from(dna[5]) * 360 / 256, saturation: 70, lightness: 40 },
+			ears,
+			mouth,
+			background: Hsl { hue: u32::from(dna[8]) * 360 / 256, saturation: 45, lightness: 90 },
+			placement,
+		}
+	}
+}
+
+/// Render the avatar of `kitty`. Only its DNA is drawn; the name is left to the caller.
+pub fn render_svg(kitty: &Kitty) -> String {
+	render_dna_svg(&kitty.dna)
+}
+
+/// Render the avatar for `dna` as a standalone SVG document.
+pub fn render_dna_svg(dna: &[u8; 16]) -> String {
+	let traits = Traits::decode(dna);
+	let mut svg = String::new();
+	// Writing to a `String` can't fail.
+	let _ = write_svg(&mut svg, &traits);
+	svg
+}
+
+fn write_svg(svg: &mut String, traits: &Traits) -> std::fmt::Result {
+	writeln!(
+		svg,
+		r#"<svg xmlns="http://www.w3.org/2000/svg" width="{SIZE}" height="{SIZE}" viewBox="0 0 {SIZE} {SIZE}">"#
+	)?;
+	writeln!(svg, r#"<rect width="{SIZE}" height="{SIZE}" fill="{}"/>"#, traits.background)?;
+	write_ears(svg, traits)?;
+	writeln!(
+		svg,
+		r#"<ellipse id="head" cx="128" cy="140" rx="84" ry="76" fill="{}" stroke="{}" stroke-width="3"/>"#,
+		traits.body,
+		traits.body.shifted(0, 30)
+	)?;
+	write_pattern(svg, traits)?;
+	write_eyes(svg, traits)?;
+	writeln!(svg, r#"<path d="M121 160 L135 160 L128 168 Z" fill="hsl(350,60%,70%)"/>"#)?;
+	write_mouth(svg, traits)?;
+	writeln!(svg, "</svg>")
+}
+
+fn write_ears(svg: &mut String, traits: &Traits) -> std::fmt::Result {
+	let fill = traits.body;
+	let stroke = traits.body.shifted(0, 30);
+	for (side, x) in [(-1i32, 76i32), (1, 180)] {
+		let d = match traits.ears {
+			Ears::Pointed => format!("M{} 96 L{} 30 L{} 84 Z", x - 30 * side, x, x + 14 * side),
+			Ears::Round => format!("M{} 96 Q{} 30 {} 84 Z", x - 30 * side, x, x + 14 * side),
+			Ears::Folded => format!(
+				"M{} 96 L{} 52 L{} 64 L{} 84 Z",
+				x - 30 * side,
+				x - 6 * side,
+				x + 16 * side,
+				x + 14 * side
+			),
+		};
+		writeln!(svg, r#"<path d="{d}" fill="{fill}" stroke="{stroke}" stroke-width="3"/>"#)?;
+	}
+	Ok(())
+}
+
+fn write_pattern(svg: &mut String, traits: &Traits) -> std::fmt::Result {
+	let color = traits.pattern_color;
+	let placement = traits.placement;
+	match traits.pattern {
+		Pattern::Plain => Ok(()),
+		Pattern::Stripes => {
+			for (i, offset) in placement.iter().take(3).enumerate() {
+				let x = 100 + 28 * i as u32;
+				let length = 18 + u32::from(*offset) % 14;
+				writeln!(
+					svg,
+					r#"<path d="M{x} 68 L{} {}" stroke="{color}" stroke-width="6" stroke-linecap="round"/>"#,
+					x + 2,
+					68 + length
+				)?;
+			}
+			Ok(())
+		},
+		Pattern::Spots => {
+			for pair in placement.chunks_exact(2) {
+				let cx = 70 + u32::from(pair[0]) % 116;
+				let cy = 90 + u32::from(pair[1]) % 30;
+				let r = 5 + u32::from(pair[0] ^ pair[1]) % 8;
+				writeln!(svg, r#"<circle cx="{cx}" cy="{cy}" r="{r}" fill="{color}"/>"#)?;
+			}
+			Ok(())
+		},
+		Pattern::Patches => {
+			let cx = if placement[0] & 1 == 0 { 92 } else { 164 };
+			let rx = 26 + u32::from(placement[1]) % 14;
+			let ry = 22 + u32::from(placement[2]) % 12;
+			writeln!(
+				svg,
+				r#"<ellipse cx="{cx}" cy="124" rx="{rx}" ry="{ry}" fill="{color}" opacity="0.8"/>"#
+			)
+		},
+	}
+}
+
+fn write_eyes(svg: &mut String, traits: &Traits) -> std::fmt::Result {
+	let color = traits.eye_color;
+	for cx in [98, 158] {
+		match traits.eyes {
+			Eyes::Round => {
+				writeln!(svg, r#"<circle cx="{cx}" cy="132" r="14" fill="white"/>"#)?;
+				writeln!(svg, r#"<circle cx="{cx}" cy="134" r="8" fill="{color}"/>"#)?;
+			},
+			Eyes::Almond => {
+				writeln!(svg, r#"<ellipse cx="{cx}" cy="132" rx="16" ry="10" fill="white"/>"#)?;
+				writeln!(svg, r#"<ellipse cx="{cx}" cy="132" rx="4" ry="9" fill="{color}"/>"#)?;
+			},
+			Eyes::Sleepy => {
+				writeln!(
+					svg,
+					r#"<path d="M{} 134 Q{cx} 144 {} 134" stroke="{color}" stroke-width="4" fill="none"/>"#,
+					cx - 14,
+					cx + 14
+				)?;
+			},
+		}
+	}
+	Ok(())
+}
+
+fn write_mouth(svg: &mut String, traits: &Traits) -> std::fmt::Result {
+	let stroke = traits.body.shifted(0, 30);
+	match traits.mouth {
+		Mouth::Smile => writeln!(
+			svg,
+			r#"<path d="M112 174 Q120 184 128 174 Q136 184 144 174" stroke="{stroke}" stroke-width="3" fill="none"/>"#
+		),
+		Mouth::Neutral => {
+			writeln!(svg, r#"<path d="M116 178 L140 178" stroke="{stroke}" stroke-width="3"/>"#)
+		},
+		Mouth::Open => {
+			writeln!(svg, r#"<ellipse cx="128" cy="180" rx="9" ry="7" fill="hsl(350,45%,35%)"/>"#)
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn rendering_is_deterministic() {
+		let dna = [7u8; 16];
+		assert_eq!(render_dna_svg(&dna), render_dna_svg(&dna));
+	}
+
+	#[test]
+	fn every_byte_value_decodes() {
+		for byte in 0..=u8::MAX {
+			let traits = Traits::decode(&[byte; 16]);
+			assert!(traits.body.hue < 360);
+			assert!(traits.pattern_color.hue < 360);
+			assert!(traits.eye_color.hue < 360);
+			assert!(traits.background.hue < 360);
+		}
+	}
+}
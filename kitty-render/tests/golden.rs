@@ -0,0 +1,49 @@
+//! Golden-file tests pinning the rendered output for a fixed set of DNA.
+//!
+//! Run with `KITTY_RENDER_BLESS=1` to rewrite the golden files after an intentional change to
+//! the artwork, and review the diff of `tests/golden` before committing it.
+
+use std::{fs, path::PathBuf};
+
+use kitty_render::render_svg;
+use pallet_kitties::Kitty;
+
+const CASES: [(&str, [u8; 16]); 5] = [
+	("zeros", [0; 16]),
+	("ones", [0xff; 16]),
+	("ascending", [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]),
+	("stripes", [0x12, 0x34, 0x56, 0x05, 0x01, 0x9a, 0x02, 0x01, 0x40, 3, 17, 250, 9, 88, 41, 7]),
+	("spots", [0xde, 0xad, 0xbe, 0xee, 0x02, 0x33, 0x01, 0x02, 0xc0, 200, 31, 77, 140, 5, 96, 222]),
+];
+
+fn golden_path(name: &str) -> PathBuf {
+	PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+		.join("tests")
+		.join("golden")
+		.join(format!("{name}.svg"))
+}
+
+#[test]
+fn renders_match_golden_files() {
+	let bless = std::env::var_os("KITTY_RENDER_BLESS").is_some();
+	for (name, dna) in CASES {
+		let svg = render_svg(&Kitty { dna, name: *b"abcdabcd" });
+		let path = golden_path(name);
+		if bless {
+			fs::write(&path, &svg).unwrap();
+			continue
+		}
+		let expected = fs::read_to_string(&path)
+			.unwrap_or_else(|e| panic!("missing golden file {}: {e}", path.display()));
+		assert_eq!(svg, expected, "rendering of `{name}` changed");
+	}
+}
+
+#[test]
+fn name_does_not_affect_rendering() {
+	let dna = CASES[2].1;
+	assert_eq!(
+		render_svg(&Kitty { dna, name: *b"abcdabcd" }),
+		render_svg(&Kitty { dna, name: *b"zzzzzzzz" })
+	);
+}
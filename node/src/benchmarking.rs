@@ -2,18 +2,23 @@
 //!
 //! Should only be used for benchmarking as it may break in other contexts.
 
-use crate::service::FullClient;
+use crate::{chain_spec::kitty_minter_seed, service::FullClient};
 
 use node_template_runtime as runtime;
-use runtime::{AccountId, Balance, BalancesCall, SystemCall};
+use pallet_kitties_runtime_api::{KittiesApi, KittyId, KittyInfo};
+use runtime::{AccountId, Balance, BalancesCall, KittiesCall, SystemCall};
 use sc_cli::Result;
 use sc_client_api::BlockBackend;
-use sp_core::{Encode, Pair};
+use sp_api::ProvideRuntimeApi;
+use sp_core::{sr25519, Encode, Pair};
 use sp_inherents::{InherentData, InherentDataProvider};
 use sp_keyring::Sr25519Keyring;
 use sp_runtime::{OpaqueExtrinsic, SaturatedConversion};
 
-use std::{sync::Arc, time::Duration};
+use std::{
+	sync::{Arc, Mutex},
+	time::Duration,
+};
 
 /// Generates extrinsics for the `benchmark overhead` command.
 ///
@@ -95,6 +100,234 @@ impl frame_benchmarking_cli::ExtrinsicBuilder for TransferKeepAliveBuilder {
 	}
 }
 
+/// Generates `KittiesModule::create` extrinsics.
+///
+/// Each extrinsic is signed by its own minter account so the per-account mint limit isn't hit,
+/// which needs a chain that funds them, like `kitties-stress`.
+///
+/// Note: Should only be used for benchmarking.
+pub struct CreateKittyBuilder {
+	client: Arc<FullClient>,
+}
+
+impl CreateKittyBuilder {
+	/// Creates a new [`Self`] from the given client.
+	pub fn new(client: Arc<FullClient>) -> Self {
+		Self { client }
+	}
+}
+
+impl frame_benchmarking_cli::ExtrinsicBuilder for CreateKittyBuilder {
+	fn pallet(&self) -> &str {
+		"kitties"
+	}
+
+	fn extrinsic(&self) -> &str {
+		"create"
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		let acc = sr25519::Pair::from_string(&format!("//{}", kitty_minter_seed(nonce)), None)
+			.map_err(|_| "Invalid minter seed")?;
+		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
+			self.client.as_ref(),
+			acc,
+			KittiesCall::create { name: *b"benchkit" }.into(),
+			0,
+		)
+		.into();
+
+		Ok(extrinsic)
+	}
+}
+
+/// Generates `KittiesModule::breed` extrinsics, breeding the first two of Bob's kitties.
+///
+/// Note: Should only be used for benchmarking.
+pub struct BreedKittyBuilder {
+	client: Arc<FullClient>,
+	kitties: BestKitties,
+}
+
+impl BreedKittyBuilder {
+	/// Creates a new [`Self`] from the given client, finding the parents among `kitties`.
+	pub fn new(client: Arc<FullClient>, kitties: BestKitties) -> Self {
+		Self { client, kitties }
+	}
+}
+
+impl frame_benchmarking_cli::ExtrinsicBuilder for BreedKittyBuilder {
+	fn pallet(&self) -> &str {
+		"kitties"
+	}
+
+	fn extrinsic(&self) -> &str {
+		"breed"
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let (kitty_id_1, kitty_id_2) = match self.kitties.ids(|info| info.owner == bob)?[..] {
+			[kitty_id_1, kitty_id_2, ..] => (kitty_id_1, kitty_id_2),
+			_ => return Err("Bob needs two kitties to breed"),
+		};
+		let acc = Sr25519Keyring::Bob.pair();
+		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
+			self.client.as_ref(),
+			acc,
+			KittiesCall::breed { kitty_id_1, kitty_id_2, name: *b"benchkit" }.into(),
+			nonce,
+		)
+		.into();
+
+		Ok(extrinsic)
+	}
+}
+
+/// Generates `KittiesModule::transfer` extrinsics, giving each of Bob's kitties to `dest`.
+///
+/// Note: Should only be used for benchmarking.
+pub struct TransferKittyBuilder {
+	client: Arc<FullClient>,
+	dest: AccountId,
+	kitties: BestKitties,
+}
+
+impl TransferKittyBuilder {
+	/// Creates a new [`Self`] from the given client, finding Bob's kitties among `kitties`.
+	pub fn new(client: Arc<FullClient>, dest: AccountId, kitties: BestKitties) -> Self {
+		Self { client, dest, kitties }
+	}
+}
+
+impl frame_benchmarking_cli::ExtrinsicBuilder for TransferKittyBuilder {
+	fn pallet(&self) -> &str {
+		"kitties"
+	}
+
+	fn extrinsic(&self) -> &str {
+		"transfer"
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let kitty_id = *self
+			.kitties
+			.ids(|info| info.owner == bob)?
+			.get(nonce as usize)
+			.ok_or("Bob has no more kitties")?;
+		let acc = Sr25519Keyring::Bob.pair();
+		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
+			self.client.as_ref(),
+			acc,
+			KittiesCall::transfer { recipient: self.dest.clone(), kitty_id }.into(),
+			nonce,
+		)
+		.into();
+
+		Ok(extrinsic)
+	}
+}
+
+/// Generates `KittiesModule::buy` extrinsics, buying every kitty on sale from someone other
+/// than Bob.
+///
+/// Note: Should only be used for benchmarking.
+pub struct BuyKittyBuilder {
+	client: Arc<FullClient>,
+	kitties: BestKitties,
+}
+
+impl BuyKittyBuilder {
+	/// Creates a new [`Self`] from the given client, finding the listings among `kitties`.
+	pub fn new(client: Arc<FullClient>, kitties: BestKitties) -> Self {
+		Self { client, kitties }
+	}
+}
+
+impl frame_benchmarking_cli::ExtrinsicBuilder for BuyKittyBuilder {
+	fn pallet(&self) -> &str {
+		"kitties"
+	}
+
+	fn extrinsic(&self) -> &str {
+		"buy"
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let kitty_id = *self
+			.kitties
+			.ids(|info| info.on_sale && info.owner != bob)?
+			.get(nonce as usize)
+			.ok_or("No more kitties on sale")?;
+		let acc = Sr25519Keyring::Bob.pair();
+		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
+			self.client.as_ref(),
+			acc,
+			KittiesCall::buy { kitty_id }.into(),
+			nonce,
+		)
+		.into();
+
+		Ok(extrinsic)
+	}
+}
+
+/// The kitties at the best block, shared by the kitty builders.
+///
+/// They are read when a builder first needs them, so only the benchmark that was picked pays for
+/// the scan, and a failed scan fails only the kitty benchmarks.
+#[derive(Clone)]
+pub struct BestKitties {
+	client: Arc<FullClient>,
+	kitties: Arc<Mutex<Option<Vec<(KittyId, KittyInfo<AccountId>)>>>>,
+}
+
+impl BestKitties {
+	/// Creates a new [`Self`] from the given client, without reading any kitty yet.
+	pub fn new(client: Arc<FullClient>) -> Self {
+		Self { client, kitties: Default::default() }
+	}
+
+	/// The ids of the kitties matching `filter`, in id order.
+	fn ids(
+		&self,
+		filter: impl Fn(&KittyInfo<AccountId>) -> bool,
+	) -> std::result::Result<Vec<KittyId>, &'static str> {
+		let mut kitties = self.kitties.lock().map_err(|_| "Reading the kitties panicked")?;
+		if kitties.is_none() {
+			*kitties = Some(best_kitties(&self.client).map_err(|e| {
+				log::error!("Failed to read the kitties at the best block: {}", e);
+				"Failed to read the kitties at the best block"
+			})?);
+		}
+		Ok(kitties
+			.iter()
+			.flatten()
+			.filter(|(_, info)| filter(info))
+			.map(|(kitty_id, _)| *kitty_id)
+			.collect())
+	}
+}
+
+/// Every kitty at the best block, in id order.
+fn best_kitties(client: &FullClient) -> Result<Vec<(KittyId, KittyInfo<AccountId>)>> {
+	let best_hash = client.chain_info().best_hash;
+	let api = client.runtime_api();
+	let next_id = api
+		.next_kitty_id(best_hash)
+		.map_err(|e| format!("reading the next kitty id: {}", e))?;
+	let mut kitties = Vec::new();
+	for kitty_id in 0..next_id {
+		let info = api
+			.kitty(best_hash, kitty_id)
+			.map_err(|e| format!("reading kitty {}: {}", kitty_id, e))?;
+		kitties.extend(info.map(|info| (kitty_id, info)));
+	}
+	Ok(kitties)
+}
+
 /// Create a transaction using the given `call`.
 ///
 /// Note: Should only be used for benchmarking.
@@ -1,12 +1,15 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	pallet_kitties::KittyId, AccountId, AuraConfig, Balance, BalancesConfig, GenesisConfig,
+	GrandpaConfig, KittiesModuleConfig, SS58Prefix, Signature, SudoConfig, SystemConfig,
+	WASM_BINARY,
 };
-use sc_service::ChainType;
+use sc_service::{ChainType, Properties};
+use serde::Deserialize;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_consensus_grandpa::AuthorityId as GrandpaId;
-use sp_core::{sr25519, Pair, Public};
+use sp_core::{crypto::Ss58Codec, hashing::blake2_128, sr25519, Pair, Public};
 use sp_runtime::traits::{IdentifyAccount, Verify};
+use std::path::Path;
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
@@ -14,6 +17,84 @@ use sp_runtime::traits::{IdentifyAccount, Verify};
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
 pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
 
+/// The balance of each pre-funded account, unless given otherwise in `--genesis-accounts`.
+const ENDOWMENT: Balance = 1 << 60;
+
+/// A kitty created at genesis: its owner, DNA and name.
+type GenesisKitty = (AccountId, [u8; 16], [u8; 8]);
+
+/// How many kitty minter accounts the `kitties-stress` preset funds, so mints can be benchmarked
+/// without running into the per-account mint limit.
+pub const KITTY_MINTERS: u32 = 10_000;
+
+/// The seed of the kitty minter account with the given index.
+pub fn kitty_minter_seed(index: u32) -> String {
+	format!("Minter//{}", index)
+}
+
+/// Settings from the command line applied to every built-in chain spec.
+#[derive(Clone)]
+pub struct SpecOptions {
+	/// Accounts to endow at genesis, overriding the balance of any the spec already funds.
+	pub genesis_accounts: Vec<(AccountId, Balance)>,
+	/// Advertised as the `tokenSymbol` property.
+	pub token_symbol: String,
+	/// Advertised as the `tokenDecimals` property.
+	pub token_decimals: u8,
+}
+
+impl SpecOptions {
+	fn properties(&self) -> Properties {
+		let mut properties = Properties::new();
+		properties.insert("tokenSymbol".into(), self.token_symbol.clone().into());
+		properties.insert("tokenDecimals".into(), self.token_decimals.into());
+		properties.insert("ss58Format".into(), SS58Prefix::get().into());
+		properties
+	}
+}
+
+#[derive(Deserialize)]
+struct GenesisAccount {
+	account: String,
+	balance: Option<Balance>,
+}
+
+/// Read the accounts to endow at genesis from a JSON file of `{ "account", "balance" }` objects,
+/// or from a CSV file of `account,balance` lines. Accounts are SS58 addresses; a missing balance
+/// defaults to that of the other pre-funded accounts.
+pub fn load_genesis_accounts(path: &Path) -> Result<Vec<(AccountId, Balance)>, String> {
+	let contents = std::fs::read_to_string(path)
+		.map_err(|e| format!("Error reading genesis accounts from {}: {}", path.display(), e))?;
+	let accounts: Vec<GenesisAccount> = if path.extension().map_or(false, |ext| ext == "json") {
+		serde_json::from_str(&contents).map_err(|e| format!("Invalid genesis accounts: {}", e))?
+	} else {
+		contents
+			.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty() && !line.starts_with('#') && *line != "account,balance")
+			.map(|line| {
+				let (account, balance) = line.split_once(',').unwrap_or((line, ""));
+				let balance = match balance.trim() {
+					"" => None,
+					balance => Some(balance.parse().map_err(|_| {
+						format!("Invalid balance `{}` in genesis accounts", balance)
+					})?),
+				};
+				Ok(GenesisAccount { account: account.trim().into(), balance })
+			})
+			.collect::<Result<_, String>>()?
+	};
+
+	accounts
+		.into_iter()
+		.map(|GenesisAccount { account, balance }| {
+			let account = AccountId::from_ss58check(&account)
+				.map_err(|e| format!("Invalid genesis account `{}`: {:?}", account, e))?;
+			Ok((account, balance.unwrap_or(ENDOWMENT)))
+		})
+		.collect()
+}
+
 /// Generate a crypto pair from seed.
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
 	TPublic::Pair::from_string(&format!("//{}", seed), None)
@@ -36,7 +117,7 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
 }
 
-pub fn development_config() -> Result<ChainSpec, String> {
+pub fn development_config(options: SpecOptions) -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
 	Ok(ChainSpec::from_genesis(
@@ -59,6 +140,11 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				options.genesis_accounts.clone(),
+				// Genesis kitties
+				vec![],
+				// Kitties on sale
+				vec![],
 				true,
 			)
 		},
@@ -70,13 +156,13 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		None,
 		None,
 		// Properties
-		None,
+		Some(options.properties()),
 		// Extensions
 		None,
 	))
 }
 
-pub fn local_testnet_config() -> Result<ChainSpec, String> {
+pub fn local_testnet_config(options: SpecOptions) -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
 	Ok(ChainSpec::from_genesis(
@@ -107,6 +193,11 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				options.genesis_accounts.clone(),
+				// Genesis kitties
+				vec![],
+				// Kitties on sale
+				vec![],
 				true,
 			)
 		},
@@ -116,30 +207,124 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 		None,
 		// Protocol ID
 		None,
+		None,
 		// Properties
+		Some(options.properties()),
+		// Extensions
+		None,
+	))
+}
+
+/// A development chain whose genesis holds `kitty_count` generated kitties, shared out among the
+/// well-known dev accounts with Alice's listed for sale, and `minters` funded minter accounts.
+fn kitties_config(
+	name: &str,
+	id: &str,
+	kitty_count: u32,
+	minters: u32,
+	options: SpecOptions,
+) -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+	let owners: Vec<AccountId> = ["Alice", "Bob", "Charlie", "Dave", "Eve", "Ferdie"]
+		.into_iter()
+		.map(get_account_id_from_seed::<sr25519::Public>)
+		.collect();
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		name,
+		// ID
+		id,
+		ChainType::Development,
+		move || {
+			testnet_genesis(
+				wasm_binary,
+				// Initial PoA authorities
+				vec![authority_keys_from_seed("Alice")],
+				// Sudo account
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Pre-funded accounts
+				owners
+					.iter()
+					.cloned()
+					.chain((0..minters).map(|index| {
+						get_account_id_from_seed::<sr25519::Public>(&kitty_minter_seed(index))
+					}))
+					.collect(),
+				options.genesis_accounts.clone(),
+				// Genesis kitties
+				generated_kitties(kitty_count, &owners),
+				// Kitties on sale
+				(0..kitty_count).step_by(owners.len()).collect(),
+				true,
+			)
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
 		None,
+		// Protocol ID
 		None,
+		None,
+		// Properties
+		Some(options.properties()),
 		// Extensions
 		None,
 	))
 }
 
+/// A development chain with a thousand kitties, for demos and UI work.
+pub fn kitties_showcase_config(options: SpecOptions) -> Result<ChainSpec, String> {
+	kitties_config("Kitties Showcase", "kitties_showcase", 1_000, 0, options)
+}
+
+/// A development chain with ten thousand kitties, for testing and benchmarking at realistic state
+/// sizes.
+pub fn kitties_stress_config(options: SpecOptions) -> Result<ChainSpec, String> {
+	kitties_config("Kitties Stress", "kitties_stress", 10_000, KITTY_MINTERS, options)
+}
+
+/// `count` kitties with DNA derived from their index, so every build of a preset is identical.
+/// Kitty `index` belongs to `owners[index % owners.len()]`.
+fn generated_kitties(count: u32, owners: &[AccountId]) -> Vec<GenesisKitty> {
+	(0..count)
+		.map(|index| {
+			let owner = owners[index as usize % owners.len()].clone();
+			let dna = blake2_128(&index.to_le_bytes());
+			let mut name = [0; 8];
+			name.copy_from_slice(format!("#{:07}", index).as_bytes());
+			(owner, dna, name)
+		})
+		.collect()
+}
+
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	genesis_accounts: Vec<(AccountId, Balance)>,
+	kitties: Vec<GenesisKitty>,
+	on_sale: Vec<KittyId>,
 	_enable_println: bool,
 ) -> GenesisConfig {
+	// Accounts from `--genesis-accounts` take precedence over the spec's own.
+	let mut balances: Vec<(AccountId, Balance)> = endowed_accounts
+		.into_iter()
+		.filter(|account| !genesis_accounts.iter().any(|(a, _)| a == account))
+		.map(|account| (account, ENDOWMENT))
+		.collect();
+	balances.extend(genesis_accounts);
+
 	GenesisConfig {
 		system: SystemConfig {
 			// Add Wasm runtime to storage.
 			code: wasm_binary.to_vec(),
 		},
 		balances: BalancesConfig {
-			// Configure endowed accounts with initial balance of 1 << 60.
-			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+			// Configure endowed accounts with initial balance of 1 << 60, or as given.
+			balances,
 		},
 		aura: AuraConfig {
 			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
@@ -149,8 +334,9 @@ fn testnet_genesis(
 		},
 		sudo: SudoConfig {
 			// Assign network admin rights.
-			key: Some(root_key),
+			key: Some(root_key.clone()),
 		},
 		transaction_payment: Default::default(),
+		kitties_module: KittiesModuleConfig { kitties, on_sale, rarity_committers: vec![root_key] },
 	}
 }
@@ -1,4 +1,35 @@
+use crate::{kitties::KittiesCmd, load_test::LoadTestCmd, render_kitty::RenderKittyCmd};
 use sc_cli::RunCmd;
+use std::{path::PathBuf, str::FromStr};
+
+/// How blocks are sealed in place of Aura and GRANDPA, for local development and testing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Sealing {
+	/// Seal a block as soon as a transaction enters the pool, and finalize it.
+	Instant,
+	/// Seal and finalize blocks only when asked to through the `engine_*` RPCs.
+	Manual,
+	/// Seal and finalize a block every given number of milliseconds.
+	Interval(u64),
+}
+
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Sealing::Instant),
+			"manual" => Ok(Sealing::Manual),
+			millis => match millis.parse() {
+				Ok(millis) if millis > 0 => Ok(Sealing::Interval(millis)),
+				_ => Err(format!(
+					"expected `instant`, `manual` or a positive interval in milliseconds, got `{}`",
+					s
+				)),
+			},
+		}
+	}
+}
 
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
@@ -7,6 +38,23 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Seal blocks with manual seal instead of Aura and GRANDPA: `instant`, `manual`, or an
+	/// interval in milliseconds. Only meant for single-node development chains.
+	#[arg(long)]
+	pub sealing: Option<Sealing>,
+
+	/// Endow the accounts in this JSON or CSV file at genesis of the built-in chain specs.
+	#[arg(long, global = true)]
+	pub genesis_accounts: Option<PathBuf>,
+
+	/// The token symbol advertised in the built-in chain specs' properties.
+	#[arg(long, global = true, default_value = "UNIT")]
+	pub token_symbol: String,
+
+	/// The token decimals advertised in the built-in chain specs' properties.
+	#[arg(long, global = true, default_value_t = 12)]
+	pub token_decimals: u8,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -50,4 +98,13 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Render the avatar of a kitty from its DNA as SVG.
+	RenderKitty(RenderKittyCmd),
+
+	/// Inspect the kitties stored in the local database.
+	Kitties(KittiesCmd),
+
+	/// Submit a mix of kitty transactions to a running node and report how it copes.
+	LoadTest(LoadTestCmd),
 }
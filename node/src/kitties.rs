@@ -0,0 +1,288 @@
+//! The `kitties` subcommand, inspecting the kitties in the local database without starting the
+//! node. Kitties are read through the `KittiesApi` runtime API at the chosen block.
+
+use crate::service::FullClient;
+use node_template_runtime::{opaque::Block, AccountId, Hash};
+use pallet_kitties_runtime_api::{KittiesApi, KittyId, KittyInfo};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{bytes::to_hex, crypto::Ss58Codec};
+use std::{collections::HashSet, sync::Arc};
+
+/// The most generations `kitties lineage` goes back.
+const MAX_LINEAGE_DEPTH: u32 = 64;
+
+/// Inspect the kitties stored in the local database.
+#[derive(Debug, clap::Parser)]
+pub struct KittiesCmd {
+	#[command(subcommand)]
+	pub query: KittiesQuery,
+
+	/// Block hash or number to read kitties at, the best block by default.
+	#[arg(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// What to show about the kitties.
+#[derive(Debug, clap::Subcommand)]
+pub enum KittiesQuery {
+	/// List every kitty, one per line.
+	List,
+	/// Show everything known about a kitty as JSON.
+	Show {
+		/// The kitty to show.
+		id: KittyId,
+	},
+	/// List the kitties owned by an account.
+	Owner {
+		/// The owner's SS58 address.
+		#[arg(value_parser = parse_account)]
+		account: AccountId,
+	},
+	/// Show the ancestors of a kitty as a tree.
+	Lineage {
+		/// The kitty whose ancestors to show.
+		id: KittyId,
+		/// How many generations to go back, at most 64.
+		#[arg(
+			long,
+			default_value_t = 8,
+			value_parser = clap::value_parser!(u32).range(..=MAX_LINEAGE_DEPTH as i64)
+		)]
+		depth: u32,
+	},
+	/// Write every kitty to stdout.
+	Export {
+		/// The output format.
+		#[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+		format: ExportFormat,
+	},
+}
+
+/// Output formats of `kitties export`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+	/// A JSON array of kitties.
+	Json,
+	/// One line per kitty, with a header line and without attributes.
+	Csv,
+}
+
+/// A kitty as it is printed, with bytes decoded for people to read.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KittyRecord {
+	id: KittyId,
+	owner: String,
+	dna: String,
+	name: String,
+	parents: Option<(KittyId, KittyId)>,
+	collection_id: u32,
+	level: u32,
+	experience: u32,
+	wins: u32,
+	losses: u32,
+	on_sale: bool,
+	metadata: String,
+	attributes: Vec<(String, String)>,
+}
+
+impl KittyRecord {
+	fn new(id: KittyId, info: KittyInfo<AccountId>) -> Self {
+		KittyRecord {
+			id,
+			owner: info.owner.to_ss58check(),
+			dna: to_hex(&info.kitty.dna, false),
+			name: text(&info.kitty.name),
+			parents: info.parents,
+			collection_id: info.collection_id,
+			level: info.progress.level,
+			experience: info.progress.experience,
+			wins: info.record.wins,
+			losses: info.record.losses,
+			on_sale: info.on_sale,
+			metadata: text(&info.metadata),
+			attributes: info.attributes.iter().map(|(k, v)| (text(k), text(v))).collect(),
+		}
+	}
+
+	const CSV_HEADER: &'static str = "id,owner,dna,name,parent_1,parent_2,collection_id,level,\
+		experience,wins,losses,on_sale,metadata";
+
+	fn csv(&self) -> String {
+		let (parent_1, parent_2) = match self.parents {
+			Some((parent_1, parent_2)) => (parent_1.to_string(), parent_2.to_string()),
+			None => Default::default(),
+		};
+		[
+			self.id.to_string(),
+			self.owner.clone(),
+			self.dna.clone(),
+			csv_field(&self.name),
+			parent_1,
+			parent_2,
+			self.collection_id.to_string(),
+			self.level.to_string(),
+			self.experience.to_string(),
+			self.wins.to_string(),
+			self.losses.to_string(),
+			self.on_sale.to_string(),
+			csv_field(&self.metadata),
+		]
+		.join(",")
+	}
+}
+
+/// Reads kitties at one block.
+struct Kitties {
+	client: Arc<FullClient>,
+	at: Hash,
+}
+
+impl Kitties {
+	fn get(&self, id: KittyId) -> sc_cli::Result<Option<KittyInfo<AccountId>>> {
+		self.client
+			.runtime_api()
+			.kitty(self.at, id)
+			.map_err(|e| format!("Failed to read kitty {}: {}", id, e).into())
+	}
+
+	/// Every kitty that hasn't been burned, in id order.
+	fn all(&self) -> sc_cli::Result<Vec<KittyRecord>> {
+		let next_id = self
+			.client
+			.runtime_api()
+			.next_kitty_id(self.at)
+			.map_err(|e| format!("Failed to read the next kitty id: {}", e))?;
+		let mut kitties = Vec::new();
+		for id in 0..next_id {
+			if let Some(info) = self.get(id)? {
+				kitties.push(KittyRecord::new(id, info));
+			}
+		}
+		Ok(kitties)
+	}
+
+	/// Print `id` and its ancestors. Kitties bred from related parents share ancestors, so an
+	/// ancestor already in `shown` is printed once more without its own ancestors.
+	fn print_lineage(
+		&self,
+		id: KittyId,
+		depth: u32,
+		indent: usize,
+		shown: &mut HashSet<KittyId>,
+	) -> sc_cli::Result<()> {
+		if !shown.insert(id) {
+			println!("{:indent$}#{} (see above)", "", id, indent = indent);
+			return Ok(())
+		}
+		let info = match self.get(id)? {
+			Some(info) => info,
+			None => {
+				println!("{:indent$}#{} (burned)", "", id, indent = indent);
+				return Ok(())
+			},
+		};
+		println!("{:indent$}#{} {}", "", id, text(&info.kitty.name), indent = indent);
+		if let (Some((parent_1, parent_2)), true) = (info.parents, depth > 0) {
+			self.print_lineage(parent_1, depth - 1, indent + 2, shown)?;
+			self.print_lineage(parent_2, depth - 1, indent + 2, shown)?;
+		}
+		Ok(())
+	}
+}
+
+impl KittiesCmd {
+	/// Run the command.
+	pub fn run(&self, client: Arc<FullClient>) -> sc_cli::Result<()> {
+		let at = match &self.at {
+			Some(block) => client.expect_block_hash_from_id(&block.parse::<Block>()?)?,
+			None => client.info().best_hash,
+		};
+		let kitties = Kitties { client, at };
+
+		match &self.query {
+			KittiesQuery::List =>
+				for kitty in kitties.all()? {
+					println!(
+						"#{:<6} {} {} {}{}",
+						kitty.id,
+						kitty.owner,
+						kitty.dna,
+						kitty.name,
+						if kitty.on_sale { " (on sale)" } else { "" }
+					);
+				},
+			KittiesQuery::Show { id } => {
+				let info = kitties.get(*id)?.ok_or_else(|| format!("No kitty #{}", id))?;
+				println!("{}", to_json(&KittyRecord::new(*id, info))?);
+			},
+			KittiesQuery::Owner { account } => {
+				let owner = account.to_ss58check();
+				for kitty in kitties.all()?.into_iter().filter(|kitty| kitty.owner == owner) {
+					println!("#{:<6} {} {}", kitty.id, kitty.dna, kitty.name);
+				}
+			},
+			KittiesQuery::Lineage { id, depth } =>
+				kitties.print_lineage(*id, *depth, 0, &mut HashSet::new())?,
+			KittiesQuery::Export { format: ExportFormat::Json } =>
+				println!("{}", to_json(&kitties.all()?)?),
+			KittiesQuery::Export { format: ExportFormat::Csv } => {
+				println!("{}", KittyRecord::CSV_HEADER);
+				for kitty in kitties.all()? {
+					println!("{}", kitty.csv());
+				}
+			},
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for KittiesCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+fn parse_account(address: &str) -> Result<AccountId, String> {
+	AccountId::from_ss58check(address).map_err(|e| format!("invalid SS58 address: {:?}", e))
+}
+
+/// Bytes as text, without the zero padding of fixed-size names.
+fn text(bytes: &[u8]) -> String {
+	String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string()
+}
+
+fn csv_field(field: &str) -> String {
+	if field.contains([',', '"', '\n']) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
+
+fn to_json<T: Serialize>(value: &T) -> sc_cli::Result<String> {
+	serde_json::to_string_pretty(value).map_err(|e| format!("Failed to encode JSON: {}", e).into())
+}
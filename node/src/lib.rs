@@ -1,3 +1,16 @@
+//! Substrate Node Template library, shared by the `node-template` binary and by tests that run
+//! the node in-process.
+
 pub mod chain_spec;
-pub mod rpc;
+#[macro_use]
 pub mod service;
+mod benchmarking;
+pub mod cli;
+mod command;
+mod kitties;
+mod load_test;
+mod remote_keystore;
+mod render_kitty;
+pub mod rpc;
+
+pub use command::run;
@@ -0,0 +1,589 @@
+//! The `load-test` subcommand, stress-testing a running node with kitty traffic.
+//!
+//! It funds a set of generated accounts, then submits a random mix of `create`, `breed`, `sale`,
+//! `buy` and `transfer` calls from them at a target rate, following each transaction until it
+//! is included. When done it prints a JSON report of throughput, inclusion latency and the
+//! reasons transactions failed.
+
+use frame_support::traits::PalletInfoAccess;
+use futures::{future::join_all, StreamExt};
+use jsonrpsee::{
+	core::client::{ClientT, SubscriptionClientT},
+	rpc_params,
+	ws_client::{WsClient, WsClientBuilder},
+};
+use node_template_runtime::{
+	self as runtime,
+	pallet_kitties::{self, KittyId},
+	AccountId, Balance, BalancesCall, Hash, KittiesCall, Runtime, RuntimeCall, RuntimeEvent,
+};
+use serde_json::{json, Value};
+use sp_core::{
+	bytes::{from_hex, to_hex},
+	crypto::Ss58Codec,
+	sr25519, Decode, Encode, Pair,
+};
+use sp_runtime::{generic::Era, DispatchError, ModuleError};
+use std::{
+	collections::{BTreeMap, HashMap, HashSet},
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+/// What each load account is given to pay for kitties and fees.
+const FUNDING: Balance = 1 << 48;
+
+/// How long to wait for funding, and for each transaction to be included.
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Submit a mix of kitty transactions to a running node and report how it copes.
+#[derive(Debug, clap::Parser)]
+pub struct LoadTestCmd {
+	/// WebSocket RPC endpoint of the node under test.
+	#[arg(long, default_value = "ws://127.0.0.1:9944")]
+	pub url: String,
+
+	/// How many accounts to generate and send transactions from.
+	#[arg(long, default_value_t = 50)]
+	pub accounts: u32,
+
+	/// Transactions to submit per second.
+	#[arg(long, default_value_t = 20)]
+	pub tps: u32,
+
+	/// How long to submit transactions for, in seconds.
+	#[arg(long, default_value_t = 60)]
+	pub duration: u64,
+
+	/// Seed of the random choice of calls, so runs can be repeated.
+	#[arg(long, default_value_t = 0)]
+	pub seed: u64,
+
+	/// Secret URI of the account that funds the generated accounts.
+	#[arg(long, default_value = "//Alice")]
+	pub funder: String,
+}
+
+impl LoadTestCmd {
+	/// Run the command.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		if self.accounts == 0 || self.tps == 0 {
+			return Err("--accounts and --tps must be positive".into())
+		}
+		let report = tokio::runtime::Runtime::new()?.block_on(self.load_test())?;
+		println!("{}", serde_json::to_string_pretty(&report).expect("JSON values encode; qed"));
+		Ok(())
+	}
+
+	async fn load_test(&self) -> Result<Value, String> {
+		let chain = Arc::new(Chain::connect(&self.url).await?);
+		let funder = sr25519::Pair::from_string(&self.funder, None)
+			.map_err(|e| format!("Invalid funder URI: {:?}", e))?;
+		let accounts: Vec<sr25519::Pair> = (0..self.accounts)
+			.map(|index| {
+				sr25519::Pair::from_string(&format!("//LoadTest//{}", index), None)
+					.expect("static values are valid; qed")
+			})
+			.collect();
+
+		eprintln!("Funding {} accounts from {}", accounts.len(), self.funder);
+		chain.fund(&funder, &accounts).await?;
+
+		let mut nonces = Vec::new();
+		for pair in &accounts {
+			nonces.push(chain.next_nonce(&pair.public().into()).await?);
+		}
+		let world = Arc::new(Mutex::new(World::new(&accounts, nonces)));
+
+		eprintln!("Submitting {} transactions per second for {}s", self.tps, self.duration);
+		let started = Instant::now();
+		let mut ticks = tokio::time::interval(Duration::from_secs(1) / self.tps);
+		let mut rng = Rng::new(self.seed);
+		let mut pending = Vec::new();
+		for tick in 0.. {
+			ticks.tick().await;
+			if started.elapsed() >= Duration::from_secs(self.duration) {
+				break
+			}
+			let index = tick % accounts.len();
+			let (name, call, kitties, nonce) = world.lock().unwrap().next(index, &mut rng);
+			let extrinsic = chain.sign(&accounts[index], call, nonce);
+			let (chain, world) = (chain.clone(), world.clone());
+			pending.push(tokio::spawn(async move {
+				// The nonce may or may not have been used by a transaction that never showed up.
+				let outcome = tokio::time::timeout(SETTLE_TIMEOUT, chain.track(&extrinsic, &world))
+					.await
+					.unwrap_or_else(|_| Outcome::failed("timeout".into(), true));
+				let mut world = world.lock().unwrap();
+				world.release(&kitties);
+				if outcome.rejected {
+					world.resync.insert(index);
+				}
+				Outcome { call: name, ..outcome }
+			}));
+
+			// Nonces are lost when the pool rejects a transaction, so catch up from the node.
+			let resync: Vec<usize> = world.lock().unwrap().resync.drain().collect();
+			for index in resync {
+				let nonce = chain.next_nonce(&accounts[index].public().into()).await?;
+				world.lock().unwrap().nonces[index] = nonce;
+			}
+		}
+		let submitting = started.elapsed();
+
+		eprintln!("Waiting for {} outstanding transactions", pending.len());
+		let outcomes: Vec<Outcome> = join_all(pending)
+			.await
+			.into_iter()
+			.map(|outcome| outcome.map_err(|e| format!("Tracking task failed: {}", e)))
+			.collect::<Result<_, _>>()?;
+
+		Ok(report(&outcomes, self.tps, submitting, started.elapsed()))
+	}
+}
+
+/// How a transaction fared.
+struct Outcome {
+	call: &'static str,
+	/// Time from submission until the transaction was in a block, if it got there.
+	latency: Option<Duration>,
+	/// Why the transaction didn't succeed, if it didn't.
+	failure: Option<String>,
+	/// Whether the pool refused the transaction, so the sender's nonce wasn't used.
+	rejected: bool,
+}
+
+impl Outcome {
+	fn failed(failure: String, rejected: bool) -> Self {
+		Outcome { call: "", latency: None, failure: Some(failure), rejected }
+	}
+}
+
+/// A connection to the node under test.
+struct Chain {
+	rpc: WsClient,
+	genesis_hash: Hash,
+	spec_version: u32,
+	transaction_version: u32,
+	kitties_index: u8,
+}
+
+impl Chain {
+	async fn connect(url: &str) -> Result<Self, String> {
+		let rpc = WsClientBuilder::default()
+			.max_concurrent_requests(usize::MAX)
+			.build(url)
+			.await
+			.map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+		let genesis_hash =
+			rpc.request("chain_getBlockHash", rpc_params![0u32]).await.map_err(rpc_error)?;
+		let version: Value =
+			rpc.request("state_getRuntimeVersion", rpc_params![]).await.map_err(rpc_error)?;
+		let version_field = |name: &str| {
+			version[name]
+				.as_u64()
+				.map(|v| v as u32)
+				.ok_or_else(|| format!("Runtime version has no {}", name))
+		};
+		let spec_version = version_field("specVersion")?;
+		let transaction_version = version_field("transactionVersion")?;
+		if spec_version != runtime::VERSION.spec_version {
+			return Err(format!(
+				"The node runs spec version {}, but this load test was built for {}",
+				spec_version,
+				runtime::VERSION.spec_version
+			))
+		}
+
+		Ok(Chain {
+			rpc,
+			genesis_hash,
+			spec_version,
+			transaction_version,
+			kitties_index: runtime::KittiesModule::index() as u8,
+		})
+	}
+
+	/// Sign `call` as `signer` for an immortal era, returning the hex-encoded extrinsic.
+	fn sign(&self, signer: &sr25519::Pair, call: RuntimeCall, nonce: u32) -> String {
+		let extra: runtime::SignedExtra = (
+			frame_system::CheckNonZeroSender::<Runtime>::new(),
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		);
+		let payload = runtime::SignedPayload::from_raw(
+			call.clone(),
+			extra.clone(),
+			(
+				(),
+				self.spec_version,
+				self.transaction_version,
+				self.genesis_hash,
+				self.genesis_hash,
+				(),
+				(),
+				(),
+			),
+		);
+		let signature = payload.using_encoded(|payload| signer.sign(payload));
+		let extrinsic = runtime::UncheckedExtrinsic::new_signed(
+			call,
+			AccountId::from(signer.public()).into(),
+			runtime::Signature::Sr25519(signature),
+			extra,
+		);
+		to_hex(&extrinsic.encode(), false)
+	}
+
+	async fn next_nonce(&self, account: &AccountId) -> Result<u32, String> {
+		self.rpc
+			.request("system_accountNextIndex", rpc_params![account.to_ss58check()])
+			.await
+			.map_err(rpc_error)
+	}
+
+	async fn storage<T: Decode>(
+		&self,
+		key: Vec<u8>,
+		at: Option<Hash>,
+	) -> Result<Option<T>, String> {
+		let value: Option<String> = self
+			.rpc
+			.request("state_getStorage", rpc_params![to_hex(&key, false), at])
+			.await
+			.map_err(rpc_error)?;
+		value
+			.map(|value| {
+				let bytes = from_hex(&value).map_err(|e| format!("Invalid storage hex: {}", e))?;
+				T::decode(&mut &bytes[..]).map_err(|e| format!("Undecodable storage: {}", e))
+			})
+			.transpose()
+	}
+
+	/// Send each account `FUNDING` from `funder` and wait until the transfers are in a block.
+	async fn fund(&self, funder: &sr25519::Pair, accounts: &[sr25519::Pair]) -> Result<(), String> {
+		let mut nonce = self.next_nonce(&funder.public().into()).await?;
+		for account in accounts {
+			let call = BalancesCall::transfer_keep_alive {
+				dest: AccountId::from(account.public()).into(),
+				value: FUNDING,
+			};
+			let extrinsic = self.sign(funder, call.into(), nonce);
+			self.rpc
+				.request::<Hash, _>("author_submitExtrinsic", rpc_params![extrinsic])
+				.await
+				.map_err(|e| format!("Funding was rejected: {}", e))?;
+			nonce += 1;
+		}
+
+		// The funder's transfers are included in nonce order, so the last account is funded last.
+		let last = accounts.last().expect("there are load accounts; qed");
+		let key = frame_system::Account::<Runtime>::hashed_key_for(AccountId::from(last.public()));
+		let started = Instant::now();
+		loop {
+			let info: Option<AccountInfo> = self.storage(key.clone(), None).await?;
+			if info.map_or(false, |info| info.data.free >= FUNDING) {
+				return Ok(())
+			}
+			if started.elapsed() > SETTLE_TIMEOUT {
+				return Err("Timed out waiting for the load accounts to be funded".into())
+			}
+			tokio::time::sleep(Duration::from_secs(1)).await;
+		}
+	}
+
+	/// Submit `extrinsic` and follow it into a block, applying its kitty events to `world`.
+	async fn track(&self, extrinsic: &str, world: &Mutex<World>) -> Outcome {
+		let submitted = Instant::now();
+		let mut statuses = match self
+			.rpc
+			.subscribe::<Value, _>(
+				"author_submitAndWatchExtrinsic",
+				rpc_params![extrinsic],
+				"author_unwatchExtrinsic",
+			)
+			.await
+		{
+			Ok(statuses) => statuses,
+			Err(e) => return Outcome::failed(rpc_error(e), true),
+		};
+
+		let block: Hash = loop {
+			let status = match statuses.next().await {
+				Some(Ok(status)) => status,
+				Some(Err(e)) => return Outcome::failed(rpc_error(e), false),
+				None => return Outcome::failed("watch ended early".into(), false),
+			};
+			if let Some(block) = status.get("inBlock") {
+				match serde_json::from_value(block.clone()) {
+					Ok(block) => break block,
+					Err(e) => return Outcome::failed(format!("bad block hash: {}", e), false),
+				}
+			}
+			if let Some(status) = status.as_str().filter(|s| ["dropped", "invalid"].contains(s)) {
+				return Outcome::failed(format!("transaction {}", status), true)
+			}
+			if status.get("usurped").is_some() {
+				return Outcome::failed("transaction usurped".into(), true)
+			}
+		};
+		let latency = submitted.elapsed();
+		let _ = statuses.unsubscribe().await;
+
+		let failure = match self.apply_events(extrinsic, block, world).await {
+			Ok(failure) => failure,
+			Err(e) => Some(e),
+		};
+		Outcome { call: "", latency: Some(latency), failure, rejected: false }
+	}
+
+	/// Find `extrinsic` in `block` and update `world` from the events it emitted, returning why
+	/// it failed to dispatch, if it did.
+	async fn apply_events(
+		&self,
+		extrinsic: &str,
+		block: Hash,
+		world: &Mutex<World>,
+	) -> Result<Option<String>, String> {
+		let body: Value = self
+			.rpc
+			.request("chain_getBlock", rpc_params![block])
+			.await
+			.map_err(rpc_error)?;
+		let index = body["block"]["extrinsics"]
+			.as_array()
+			.and_then(|extrinsics| extrinsics.iter().position(|e| e.as_str() == Some(extrinsic)))
+			.ok_or("extrinsic missing from its block")? as u32;
+
+		let key = frame_support::storage::storage_prefix(b"System", b"Events").to_vec();
+		let records: Vec<frame_system::EventRecord<RuntimeEvent, Hash>> =
+			self.storage(key, Some(block)).await?.unwrap_or_default();
+		let mut world = world.lock().unwrap();
+		for record in records {
+			if record.phase != frame_system::Phase::ApplyExtrinsic(index) {
+				continue
+			}
+			match record.event {
+				RuntimeEvent::System(frame_system::Event::ExtrinsicFailed {
+					dispatch_error,
+					..
+				}) => return Ok(Some(self.describe(dispatch_error))),
+				RuntimeEvent::KittiesModule(event) => world.apply(event),
+				_ => {},
+			}
+		}
+		Ok(None)
+	}
+
+	/// Name kitty errors, which make up most failures, and show the rest as they are.
+	fn describe(&self, error: DispatchError) -> String {
+		match error {
+			DispatchError::Module(ModuleError { index, error, .. })
+				if index == self.kitties_index =>
+				match pallet_kitties::Error::<Runtime>::decode(&mut &error[..]) {
+					Ok(error) => format!("KittiesModule::{:?}", error),
+					Err(_) => format!("{:?}", error),
+				},
+			error => format!("{:?}", error),
+		}
+	}
+}
+
+type AccountInfo = frame_system::AccountInfo<runtime::Index, pallet_balances::AccountData<Balance>>;
+
+/// What the load test believes about the chain, used to pick calls that should succeed.
+struct World {
+	accounts: Vec<AccountId>,
+	indices: HashMap<AccountId, usize>,
+	nonces: Vec<u32>,
+	owned: Vec<Vec<KittyId>>,
+	on_sale: BTreeMap<KittyId, usize>,
+	/// Kitties with a transaction in flight, which no other transaction may use.
+	busy: HashSet<KittyId>,
+	/// Accounts whose nonce must be fetched from the node again.
+	resync: HashSet<usize>,
+}
+
+impl World {
+	fn new(accounts: &[sr25519::Pair], nonces: Vec<u32>) -> Self {
+		let accounts: Vec<AccountId> = accounts.iter().map(|pair| pair.public().into()).collect();
+		World {
+			indices: accounts
+				.iter()
+				.cloned()
+				.enumerate()
+				.map(|(i, account)| (account, i))
+				.collect(),
+			owned: vec![Vec::new(); accounts.len()],
+			accounts,
+			nonces,
+			on_sale: BTreeMap::new(),
+			busy: HashSet::new(),
+			resync: HashSet::new(),
+		}
+	}
+
+	/// Pick a call for account `index` to send, returning its name, the kitties it uses and the
+	/// nonce to send it with.
+	fn next(
+		&mut self,
+		index: usize,
+		rng: &mut Rng,
+	) -> (&'static str, RuntimeCall, Vec<KittyId>, u32) {
+		let idle: Vec<KittyId> =
+			self.owned[index].iter().copied().filter(|id| !self.busy.contains(id)).collect();
+		let for_sale: Vec<KittyId> = self
+			.on_sale
+			.iter()
+			.filter(|(id, seller)| **seller != index && !self.busy.contains(id))
+			.map(|(id, _)| *id)
+			.collect();
+
+		let (name, call, kitties): (_, KittiesCall<Runtime>, _) = match rng.below(100) {
+			30..=49 if idle.len() >= 2 => {
+				let first = rng.below(idle.len());
+				let second = (first + 1 + rng.below(idle.len() - 1)) % idle.len();
+				let (kitty_id_1, kitty_id_2) = (idle[first], idle[second]);
+				let call = KittiesCall::breed { kitty_id_1, kitty_id_2, name: *b"loadtest" };
+				("breed", call, vec![kitty_id_1, kitty_id_2])
+			},
+			50..=64 if !idle.is_empty() => {
+				let kitty_id = idle[rng.below(idle.len())];
+				("sale", KittiesCall::sale { kitty_id }, vec![kitty_id])
+			},
+			65..=79 if !for_sale.is_empty() => {
+				let kitty_id = for_sale[rng.below(for_sale.len())];
+				("buy", KittiesCall::buy { kitty_id }, vec![kitty_id])
+			},
+			80..=99 if !idle.is_empty() => {
+				let kitty_id = idle[rng.below(idle.len())];
+				let recipient = self.accounts[(index + 1) % self.accounts.len()].clone();
+				("transfer", KittiesCall::transfer { recipient, kitty_id }, vec![kitty_id])
+			},
+			_ => ("create", KittiesCall::create { name: *b"loadtest" }, vec![]),
+		};
+
+		self.busy.extend(&kitties);
+		let nonce = self.nonces[index];
+		self.nonces[index] += 1;
+		(name, call.into(), kitties, nonce)
+	}
+
+	fn release(&mut self, kitties: &[KittyId]) {
+		for kitty_id in kitties {
+			self.busy.remove(kitty_id);
+		}
+	}
+
+	fn apply(&mut self, event: pallet_kitties::Event<Runtime>) {
+		use pallet_kitties::Event::*;
+		match event {
+			KittyCreated { who, kitty_id, .. } | KittyBred { who, kitty_id, .. } =>
+				self.give(kitty_id, &who),
+			KittyOnSale { who, kitty_id, .. } =>
+				if let Some(&index) = self.indices.get(&who) {
+					self.on_sale.insert(kitty_id, index);
+				},
+			KittyBought { who, kitty_id, .. } => {
+				self.on_sale.remove(&kitty_id);
+				self.give(kitty_id, &who);
+			},
+			KittyTransferred { recipient, kitty_id, .. } => {
+				self.on_sale.remove(&kitty_id);
+				self.give(kitty_id, &recipient);
+			},
+			_ => {},
+		}
+	}
+
+	/// Record `who` as the owner of `kitty_id`, forgetting any earlier owner.
+	fn give(&mut self, kitty_id: KittyId, who: &AccountId) {
+		for owned in &mut self.owned {
+			owned.retain(|id| *id != kitty_id);
+		}
+		if let Some(&index) = self.indices.get(who) {
+			self.owned[index].push(kitty_id);
+		}
+	}
+}
+
+/// A small xorshift generator, so a seed reproduces the same mix of calls.
+struct Rng(u64);
+
+impl Rng {
+	fn new(seed: u64) -> Self {
+		// xorshift is stuck at zero, so never start there
+		Rng((seed ^ 0x9e37_79b9_7f4a_7c15).max(1))
+	}
+
+	fn below(&mut self, bound: usize) -> usize {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		(self.0 % bound as u64) as usize
+	}
+}
+
+fn report(outcomes: &[Outcome], tps: u32, submitting: Duration, total: Duration) -> Value {
+	let mut latencies: Vec<u128> =
+		outcomes.iter().filter_map(|o| o.latency).map(|l| l.as_millis()).collect();
+	latencies.sort_unstable();
+	let percentile = |p: usize| {
+		latencies
+			.get((latencies.len() * p / 100).min(latencies.len().saturating_sub(1)))
+			.copied()
+	};
+	let mean =
+		(!latencies.is_empty()).then(|| latencies.iter().sum::<u128>() / latencies.len() as u128);
+
+	let mut calls: BTreeMap<&str, (u32, u32, u32)> = BTreeMap::new();
+	let mut failures: BTreeMap<&str, u32> = BTreeMap::new();
+	for outcome in outcomes {
+		let (submitted, succeeded, failed) = calls.entry(outcome.call).or_default();
+		*submitted += 1;
+		match &outcome.failure {
+			Some(failure) => {
+				*failed += 1;
+				*failures.entry(failure.as_str()).or_default() += 1;
+			},
+			None => *succeeded += 1,
+		}
+	}
+	let included = latencies.len();
+	let succeeded = outcomes.iter().filter(|o| o.failure.is_none()).count();
+
+	json!({
+		"targetTps": tps,
+		"submittingSecs": submitting.as_secs_f64(),
+		"totalSecs": total.as_secs_f64(),
+		"submitted": outcomes.len(),
+		"included": included,
+		"succeeded": succeeded,
+		"failed": outcomes.len() - succeeded,
+		"includedTps": included as f64 / total.as_secs_f64(),
+		"latencyMs": {
+			"min": latencies.first(),
+			"mean": mean,
+			"p50": percentile(50),
+			"p95": percentile(95),
+			"p99": percentile(99),
+			"max": latencies.last(),
+		},
+		"calls": calls
+			.into_iter()
+			.map(|(call, (submitted, succeeded, failed))| {
+				(call, json!({ "submitted": submitted, "succeeded": succeeded, "failed": failed }))
+			})
+			.collect::<BTreeMap<_, _>>(),
+		"failures": failures,
+	})
+}
+
+fn rpc_error(error: jsonrpsee::core::Error) -> String {
+	format!("RPC error: {}", error)
+}
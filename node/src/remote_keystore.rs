@@ -0,0 +1,595 @@
+//! A keystore whose keys are held by a signer outside the node, selected with
+//! `--keystore-uri unix://<path>`.
+//!
+//! The node talks to the signer over a Unix socket, sending one JSON-RPC 2.0 request per
+//! connection as a single line and reading a single line back. Keys are identified by their
+//! four-character key type (e.g. `aura`, `gran`) and crypto type (`sr25`, `ed25`, `ecds`), and
+//! all keys, messages and signatures are `0x`-prefixed hex. The signer implements:
+//!
+//! | method                   | params                                      | result           |
+//! |--------------------------|---------------------------------------------|------------------|
+//! | `keystore_publicKeys`    | `keyType`, `crypto`                         | `[public]`       |
+//! | `keystore_generate`      | `keyType`, `crypto`, `seed` (optional)      | `public`         |
+//! | `keystore_insert`        | `keyType`, `suri`, `public`                 | `null`           |
+//! | `keystore_hasKeys`       | `keys`: `[[keyType, public]]`               | `bool`           |
+//! | `keystore_sign`          | `keyType`, `crypto`, `public`, `message`    | `signature`/null |
+//! | `keystore_signPrehashed` | `keyType`, `public`, `message` (ECDSA only) | `signature`/null |
+//!
+//! A `null` signature means the signer doesn't hold the key.
+//!
+//! Requests block for up to ten seconds, so the async `CryptoStore` methods send them from the
+//! blocking thread pool rather than from the executor. The `SyncCryptoStore` methods, which Aura
+//! and GRANDPA sign through, have to answer on the calling thread: on a worker of a
+//! multi-threaded Tokio runtime they move its other tasks to a new worker while they wait
+//! (`block_in_place`), but anywhere else they block the thread they're called on.
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use sp_core::{
+	bytes::{from_hex, to_hex},
+	crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+	ecdsa, ed25519, sr25519,
+};
+use sp_keystore::{
+	vrf::{VRFSignature, VRFTranscriptData},
+	CryptoStore, Error, SyncCryptoStore,
+};
+use std::{
+	io::{BufRead, BufReader, Write},
+	os::unix::net::UnixStream,
+	path::PathBuf,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+use tokio::runtime::RuntimeFlavor;
+
+/// How long to wait for the signer to answer a request.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The crypto types the remote signer can hold keys of.
+const CRYPTO_IDS: [CryptoTypeId; 3] = [sr25519::CRYPTO_ID, ed25519::CRYPTO_ID, ecdsa::CRYPTO_ID];
+
+#[derive(Deserialize)]
+struct Response {
+	result: Option<Value>,
+	error: Option<ResponseError>,
+}
+
+#[derive(Deserialize)]
+struct ResponseError {
+	code: i64,
+	message: String,
+}
+
+/// A `CryptoStore` and `SyncCryptoStore` backed by a remote signer. Clones share the request
+/// ids.
+#[derive(Clone)]
+pub struct RemoteKeystore {
+	path: PathBuf,
+	next_id: Arc<AtomicU64>,
+}
+
+impl RemoteKeystore {
+	/// Connect to the signer listening at `uri`, failing if it can't be reached.
+	pub fn open(uri: &str) -> Result<Self, String> {
+		let path = uri
+			.strip_prefix("unix://")
+			.ok_or_else(|| format!("unsupported keystore URI `{}`, expected unix://<path>", uri))?;
+		let keystore = RemoteKeystore { path: path.into(), next_id: Arc::new(AtomicU64::new(0)) };
+		// Check the signer is reachable now rather than when the first block needs signing.
+		keystore
+			.public_keys(sp_core::crypto::key_types::AURA, sr25519::CRYPTO_ID)
+			.map_err(|e| e.to_string())?;
+		Ok(keystore)
+	}
+
+	fn call<R: DeserializeOwned>(&self, method: &str, params: Value) -> Result<R, Error> {
+		// `block_in_place` panics on a current-thread runtime, so only use it where it can help
+		match tokio::runtime::Handle::try_current().map(|handle| handle.runtime_flavor()) {
+			Ok(RuntimeFlavor::MultiThread) =>
+				tokio::task::block_in_place(|| self.request(method, params)),
+			_ => self.request(method, params),
+		}
+	}
+
+	fn request<R: DeserializeOwned>(&self, method: &str, params: Value) -> Result<R, Error> {
+		let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+		let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+		let other = |e: &dyn std::fmt::Display| {
+			Error::Other(format!("remote signer at {}: {}", self.path.display(), e))
+		};
+
+		let mut stream = UnixStream::connect(&self.path).map_err(|e| other(&e))?;
+		stream.set_read_timeout(Some(TIMEOUT)).map_err(|e| other(&e))?;
+		stream.set_write_timeout(Some(TIMEOUT)).map_err(|e| other(&e))?;
+		writeln!(stream, "{}", request).map_err(|e| other(&e))?;
+
+		let mut line = String::new();
+		BufReader::new(stream).read_line(&mut line).map_err(|e| other(&e))?;
+		let response: Response = serde_json::from_str(&line).map_err(|e| other(&e))?;
+		if let Some(error) = response.error {
+			return Err(other(&format!("{} (code {})", error.message, error.code)))
+		}
+		serde_json::from_value(response.result.unwrap_or(Value::Null)).map_err(|e| other(&e))
+	}
+
+	/// Run `request` on the blocking thread pool, so waiting for the signer doesn't hold up the
+	/// async executor.
+	async fn blocking<R: Send + 'static>(
+		&self,
+		request: impl FnOnce(&RemoteKeystore) -> Result<R, Error> + Send + 'static,
+	) -> Result<R, Error> {
+		let keystore = self.clone();
+		tokio::task::spawn_blocking(move || request(&keystore))
+			.await
+			.map_err(|e| Error::Other(format!("remote signer request panicked: {}", e)))?
+	}
+
+	fn public_keys(&self, id: KeyTypeId, crypto: CryptoTypeId) -> Result<Vec<Vec<u8>>, Error> {
+		let keys: Vec<String> = self.call(
+			"keystore_publicKeys",
+			json!({ "keyType": key_type(id), "crypto": crypto_type(crypto) }),
+		)?;
+		keys.iter().map(|key| decode_hex(key)).collect()
+	}
+
+	fn generate(
+		&self,
+		id: KeyTypeId,
+		crypto: CryptoTypeId,
+		seed: Option<&str>,
+	) -> Result<Vec<u8>, Error> {
+		let public: String = self.call(
+			"keystore_generate",
+			json!({ "keyType": key_type(id), "crypto": crypto_type(crypto), "seed": seed }),
+		)?;
+		decode_hex(&public)
+	}
+
+	fn sign(
+		&self,
+		method: &str,
+		id: KeyTypeId,
+		crypto: CryptoTypeId,
+		public: &[u8],
+		message: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let signature: Option<String> = self.call(
+			method,
+			json!({
+				"keyType": key_type(id),
+				"crypto": crypto_type(crypto),
+				"public": to_hex(public, false),
+				"message": to_hex(message, false),
+			}),
+		)?;
+		signature.as_deref().map(decode_hex).transpose()
+	}
+}
+
+fn key_type(id: KeyTypeId) -> String {
+	String::from_utf8_lossy(&id.0).into_owned()
+}
+
+fn crypto_type(crypto: CryptoTypeId) -> String {
+	String::from_utf8_lossy(&crypto.0).into_owned()
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, Error> {
+	from_hex(hex).map_err(|e| Error::ValidationError(format!("invalid hex from signer: {}", e)))
+}
+
+/// Keep the keys that decode as `T`, logging a failed request as having no keys.
+fn typed_keys<T: for<'a> TryFrom<&'a [u8]>>(keys: Result<Vec<Vec<u8>>, Error>) -> Vec<T> {
+	match keys {
+		Ok(keys) => keys.iter().filter_map(|key| T::try_from(key.as_slice()).ok()).collect(),
+		Err(e) => {
+			log::warn!("Failed to list remote keys: {}", e);
+			Vec::new()
+		},
+	}
+}
+
+fn typed_key<T: for<'a> TryFrom<&'a [u8]>>(key: Vec<u8>) -> Result<T, Error> {
+	T::try_from(key.as_slice())
+		.map_err(|_| Error::ValidationError("invalid public key from signer".into()))
+}
+
+impl SyncCryptoStore for RemoteKeystore {
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		typed_keys(self.public_keys(id, sr25519::CRYPTO_ID))
+	}
+
+	fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		typed_key(self.generate(id, sr25519::CRYPTO_ID, seed)?)
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		typed_keys(self.public_keys(id, ed25519::CRYPTO_ID))
+	}
+
+	fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		typed_key(self.generate(id, ed25519::CRYPTO_ID, seed)?)
+	}
+
+	fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		typed_keys(self.public_keys(id, ecdsa::CRYPTO_ID))
+	}
+
+	fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		typed_key(self.generate(id, ecdsa::CRYPTO_ID, seed)?)
+	}
+
+	fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		self.call::<Value>(
+			"keystore_insert",
+			json!({ "keyType": key_type(id), "suri": suri, "public": to_hex(public, false) }),
+		)
+		.map(drop)
+		.map_err(|e| log::warn!("Failed to insert remote key: {}", e))
+	}
+
+	fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let held = SyncCryptoStore::keys(self, id)?;
+		Ok(keys.into_iter().filter(|key| held.contains(key)).collect())
+	}
+
+	fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let mut keys = Vec::new();
+		for crypto in CRYPTO_IDS {
+			keys.extend(
+				self.public_keys(id, crypto)?
+					.into_iter()
+					.map(|public| CryptoTypePublicPair(crypto, public)),
+			);
+		}
+		Ok(keys)
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let keys: Vec<_> = public_keys
+			.iter()
+			.map(|(public, id)| (key_type(*id), to_hex(public, false)))
+			.collect();
+		self.call("keystore_hasKeys", json!({ "keys": keys })).unwrap_or_else(|e| {
+			log::warn!("Failed to look up remote keys: {}", e);
+			false
+		})
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		if !CRYPTO_IDS.contains(&key.0) {
+			return Err(Error::KeyNotSupported(id))
+		}
+		self.sign("keystore_sign", id, key.0, &key.1, msg)
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		_key_type: KeyTypeId,
+		_public: &sr25519::Public,
+		_transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		Err(Error::Other("VRF signing is not supported by the remote signer".into()))
+	}
+
+	fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		self.sign("keystore_signPrehashed", id, ecdsa::CRYPTO_ID, public.as_ref(), msg)?
+			.map(typed_key)
+			.transpose()
+	}
+}
+
+#[async_trait]
+impl CryptoStore for RemoteKeystore {
+	async fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		typed_keys(self.blocking(move |remote| remote.public_keys(id, sr25519::CRYPTO_ID)).await)
+	}
+
+	async fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		typed_key(
+			self.blocking(move |remote| remote.generate(id, sr25519::CRYPTO_ID, seed.as_deref()))
+				.await?,
+		)
+	}
+
+	async fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		typed_keys(self.blocking(move |remote| remote.public_keys(id, ed25519::CRYPTO_ID)).await)
+	}
+
+	async fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		typed_key(
+			self.blocking(move |remote| remote.generate(id, ed25519::CRYPTO_ID, seed.as_deref()))
+				.await?,
+		)
+	}
+
+	async fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		typed_keys(self.blocking(move |remote| remote.public_keys(id, ecdsa::CRYPTO_ID)).await)
+	}
+
+	async fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		typed_key(
+			self.blocking(move |remote| remote.generate(id, ecdsa::CRYPTO_ID, seed.as_deref()))
+				.await?,
+		)
+	}
+
+	async fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		let (suri, public) = (suri.to_owned(), public.to_vec());
+		self.blocking(move |remote| Ok(SyncCryptoStore::insert_unknown(remote, id, &suri, &public)))
+			.await
+			.map_err(|e| log::warn!("Failed to insert remote key: {}", e))?
+	}
+
+	async fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |remote| SyncCryptoStore::supported_keys(remote, id, keys))
+			.await
+	}
+
+	async fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |remote| SyncCryptoStore::keys(remote, id)).await
+	}
+
+	async fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let public_keys = public_keys.to_vec();
+		self.blocking(move |remote| Ok(SyncCryptoStore::has_keys(remote, &public_keys)))
+			.await
+			.unwrap_or_else(|e| {
+				log::warn!("Failed to look up remote keys: {}", e);
+				false
+			})
+	}
+
+	async fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let (key, msg) = (key.clone(), msg.to_vec());
+		self.blocking(move |remote| SyncCryptoStore::sign_with(remote, id, &key, &msg))
+			.await
+	}
+
+	async fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		SyncCryptoStore::sr25519_vrf_sign(self, key_type, public, transcript_data)
+	}
+
+	async fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		let (public, msg) = (*public, *msg);
+		self.blocking(move |remote| {
+			SyncCryptoStore::ecdsa_sign_prehashed(remote, id, &public, &msg)
+		})
+		.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_keystore::LocalKeystore;
+	use sp_core::{
+		crypto::key_types::{AURA, GRANDPA},
+		Pair,
+	};
+	use std::{os::unix::net::UnixListener, thread};
+
+	/// A signer holding its keys in an in-memory keystore, serving requests on a fresh socket.
+	fn mock_signer() -> (RemoteKeystore, Arc<LocalKeystore>) {
+		static NEXT_SOCKET: AtomicU64 = AtomicU64::new(0);
+		let path = std::env::temp_dir().join(format!(
+			"kitties-signer-{}-{}.sock",
+			std::process::id(),
+			NEXT_SOCKET.fetch_add(1, Ordering::Relaxed)
+		));
+		let _ = std::fs::remove_file(&path);
+		let listener = UnixListener::bind(&path).unwrap();
+		let keystore = Arc::new(LocalKeystore::in_memory());
+
+		let signer_keystore = keystore.clone();
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				let mut stream = stream.unwrap();
+				let mut line = String::new();
+				BufReader::new(&stream).read_line(&mut line).unwrap();
+				let request: Value = serde_json::from_str(&line).unwrap();
+				let response = match handle(&signer_keystore, &request) {
+					Ok(result) =>
+						json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+					Err((code, message)) => json!({
+						"jsonrpc": "2.0",
+						"id": request["id"],
+						"error": { "code": code, "message": message },
+					}),
+				};
+				writeln!(stream, "{}", response).unwrap();
+			}
+		});
+
+		(RemoteKeystore::open(&format!("unix://{}", path.display())).unwrap(), keystore)
+	}
+
+	/// Answer `request`, or fail with a JSON-RPC error code and message.
+	fn handle(keystore: &LocalKeystore, request: &Value) -> Result<Value, (i64, String)> {
+		let params = &request["params"];
+		let id = KeyTypeId::try_from(params["keyType"].as_str().unwrap_or_default()).unwrap();
+		let crypto = params["crypto"]
+			.as_str()
+			.map(|crypto| CryptoTypeId(crypto.as_bytes().try_into().unwrap()));
+		let hex = |bytes: &[u8]| Value::String(to_hex(bytes, false));
+		let param = |name: &str| from_hex(params[name].as_str().unwrap()).unwrap();
+
+		Ok(match request["method"].as_str().unwrap() {
+			"keystore_publicKeys" => SyncCryptoStore::keys(keystore, id)
+				.unwrap()
+				.into_iter()
+				.filter(|key| Some(key.0) == crypto)
+				.map(|key| hex(&key.1))
+				.collect(),
+			"keystore_generate" => match crypto.unwrap() {
+				sr25519::CRYPTO_ID =>
+					hex(SyncCryptoStore::sr25519_generate_new(keystore, id, None).unwrap().as_ref()),
+				ed25519::CRYPTO_ID =>
+					hex(SyncCryptoStore::ed25519_generate_new(keystore, id, None).unwrap().as_ref()),
+				_ => return Err((-32602, "unsupported crypto type".into())),
+			},
+			"keystore_sign" => {
+				let key = CryptoTypePublicPair(crypto.unwrap(), param("public"));
+				SyncCryptoStore::sign_with(keystore, id, &key, &param("message"))
+					.unwrap()
+					.map_or(Value::Null, |signature| hex(&signature))
+			},
+			method => return Err((-32601, format!("method not found: {}", method))),
+		})
+	}
+
+	#[test]
+	fn aura_keys_sign_through_remote_signer() {
+		let (remote, signer) = mock_signer();
+		let message = b"aura pre-digest";
+
+		let public = SyncCryptoStore::sr25519_generate_new(&remote, AURA, None).unwrap();
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&remote, AURA), vec![public]);
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&*signer, AURA), vec![public]);
+		assert!(SyncCryptoStore::ed25519_public_keys(&remote, AURA).is_empty());
+
+		let signature = SyncCryptoStore::sign_with(&remote, AURA, &public.into(), message)
+			.unwrap()
+			.expect("the signer holds the key");
+		let signature = sr25519::Signature::try_from(signature.as_slice()).unwrap();
+		assert!(sr25519::Pair::verify(&signature, message, &public));
+	}
+
+	#[test]
+	fn grandpa_keys_sign_through_remote_signer() {
+		let (remote, _signer) = mock_signer();
+		let message = b"grandpa precommit";
+
+		let public = SyncCryptoStore::ed25519_generate_new(&remote, GRANDPA, None).unwrap();
+		assert_eq!(
+			SyncCryptoStore::keys(&remote, GRANDPA).unwrap(),
+			vec![CryptoTypePublicPair::from(public)]
+		);
+
+		let signature = SyncCryptoStore::sign_with(&remote, GRANDPA, &public.into(), message)
+			.unwrap()
+			.expect("the signer holds the key");
+		let signature = ed25519::Signature::try_from(signature.as_slice()).unwrap();
+		assert!(ed25519::Pair::verify(&signature, message, &public));
+
+		// keys the signer doesn't hold can't sign
+		let unknown = ed25519::Pair::generate().0.public();
+		assert!(matches!(
+			SyncCryptoStore::sign_with(&remote, GRANDPA, &unknown.into(), message),
+			Ok(None)
+		));
+	}
+
+	#[tokio::test]
+	async fn async_requests_run_off_the_executor() {
+		let (remote, signer) = mock_signer();
+		let message = b"aura pre-digest";
+
+		let public = CryptoStore::sr25519_generate_new(&remote, AURA, None).await.unwrap();
+		assert_eq!(CryptoStore::sr25519_public_keys(&remote, AURA).await, vec![public]);
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&*signer, AURA), vec![public]);
+
+		let signature = CryptoStore::sign_with(&remote, AURA, &public.into(), message)
+			.await
+			.unwrap()
+			.expect("the signer holds the key");
+		let signature = sr25519::Signature::try_from(signature.as_slice()).unwrap();
+		assert!(sr25519::Pair::verify(&signature, message, &public));
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn sync_requests_leave_the_worker_to_other_tasks() {
+		let (remote, _signer) = mock_signer();
+		let message = b"grandpa precommit";
+
+		// called on a worker thread, as block import and GRANDPA voting do
+		let public = SyncCryptoStore::ed25519_generate_new(&remote, GRANDPA, None).unwrap();
+		let signature = SyncCryptoStore::sign_with(&remote, GRANDPA, &public.into(), message)
+			.unwrap()
+			.expect("the signer holds the key");
+		let signature = ed25519::Signature::try_from(signature.as_slice()).unwrap();
+		assert!(ed25519::Pair::verify(&signature, message, &public));
+	}
+
+	#[test]
+	fn signer_errors_are_reported() {
+		let (remote, _signer) = mock_signer();
+
+		let error = SyncCryptoStore::ecdsa_generate_new(&remote, AURA, None).unwrap_err();
+		assert!(error.to_string().contains("unsupported crypto type (code -32602)"));
+		let error = SyncCryptoStore::insert_unknown(&remote, AURA, "//Alice", &[0; 32]);
+		assert_eq!(error, Err(()));
+		assert!(!SyncCryptoStore::has_keys(&remote, &[(vec![0; 32], AURA)]));
+	}
+
+	#[test]
+	fn unreachable_signer_is_rejected_at_startup() {
+		assert!(RemoteKeystore::open("unix:///nonexistent/signer.sock").is_err());
+		assert!(RemoteKeystore::open("https://signer.example").is_err());
+	}
+}
@@ -0,0 +1,34 @@
+//! The `render-kitty` subcommand, previewing kitty avatars without a running chain.
+
+use std::{fs, io::Write, path::PathBuf};
+
+/// Render the avatar of a kitty from its DNA as SVG.
+#[derive(Debug, clap::Parser)]
+pub struct RenderKittyCmd {
+	/// The 16 bytes of kitty DNA, hex encoded with an optional `0x` prefix.
+	#[arg(long, value_parser = parse_dna)]
+	pub dna: [u8; 16],
+
+	/// Write the SVG to this file instead of stdout.
+	#[arg(long, short)]
+	pub output: Option<PathBuf>,
+}
+
+impl RenderKittyCmd {
+	/// Run the command.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let svg = kitty_render::render_dna_svg(&self.dna);
+		match &self.output {
+			Some(path) => fs::write(path, svg)?,
+			None => std::io::stdout().write_all(svg.as_bytes())?,
+		}
+		Ok(())
+	}
+}
+
+fn parse_dna(hex: &str) -> Result<[u8; 16], String> {
+	let bytes = sp_core::bytes::from_hex(hex).map_err(|e| format!("invalid hex: {e}"))?;
+	bytes
+		.try_into()
+		.map_err(|bytes: Vec<u8>| format!("DNA must be 16 bytes, got {}", bytes.len()))
+}
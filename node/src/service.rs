@@ -1,15 +1,27 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use node_template_runtime::{self, opaque::Block, RuntimeApi};
+use crate::{cli::Sealing, remote_keystore::RemoteKeystore};
+use futures::{channel::mpsc, prelude::*, stream};
+use node_template_runtime::{self, opaque::Block, Hash, RuntimeApi};
 use sc_client_api::BlockBackend;
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 use sc_consensus_grandpa::SharedVoterState;
+use sc_consensus_manual_seal::{
+	consensus::aura::AuraConsensusDataProvider, EngineCommand, InstantSealParams, ManualSealParams,
+};
 pub use sc_executor::NativeElseWasmExecutor;
-use sc_keystore::LocalKeystore;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager, WarpSyncParams};
 use sc_telemetry::{Telemetry, TelemetryWorker};
-use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use sp_blockchain::HeaderBackend;
+use sp_consensus_aura::sr25519::{AuthorityPair as AuraPair, AuthoritySignature as AuraSignature};
+use std::{
+	pin::Pin,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -38,6 +50,7 @@ type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 
 pub fn new_partial(
 	config: &Configuration,
+	sealing: Option<Sealing>,
 ) -> Result<
 	sc_service::PartialComponents<
 		FullClient,
@@ -58,10 +71,6 @@ pub fn new_partial(
 	>,
 	ServiceError,
 > {
-	if config.keystore_remote.is_some() {
-		return Err(ServiceError::Other("Remote Keystores are not supported.".into()))
-	}
-
 	let telemetry = config
 		.telemetry_endpoints
 		.clone()
@@ -110,6 +119,26 @@ pub fn new_partial(
 		telemetry.as_ref().map(|x| x.handle()),
 	)?;
 
+	// Manually sealed blocks carry no Aura seal, so they are imported without verification.
+	if sealing.is_some() {
+		let import_queue = sc_consensus_manual_seal::import_queue(
+			Box::new(client.clone()),
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+		);
+
+		return Ok(sc_service::PartialComponents {
+			client,
+			backend,
+			task_manager,
+			import_queue,
+			keystore_container,
+			select_chain,
+			transaction_pool,
+			other: (grandpa_block_import, grandpa_link, telemetry),
+		})
+	}
+
 	let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
 
 	let import_queue =
@@ -147,15 +176,16 @@ pub fn new_partial(
 	})
 }
 
-fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
-	// FIXME: here would the concrete keystore be built,
-	//        must return a concrete type (NOT `LocalKeystore`) that
-	//        implements `CryptoStore` and `SyncCryptoStore`
-	Err("Remote Keystore not supported.")
+fn remote_keystore(url: &str) -> Result<Arc<RemoteKeystore>, String> {
+	RemoteKeystore::open(url).map(Arc::new)
 }
 
-/// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+/// Builds a new service for a full client, authoring blocks with Aura and finalizing them with
+/// GRANDPA unless `sealing` selects manual seal.
+pub fn new_full(
+	mut config: Configuration,
+	sealing: Option<Sealing>,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -165,7 +195,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		select_chain,
 		transaction_pool,
 		other: (block_import, grandpa_link, mut telemetry),
-	} = new_partial(&config)?;
+	} = new_partial(&config, sealing)?;
 
 	if let Some(url) = &config.keystore_remote {
 		match remote_keystore(url) {
@@ -204,6 +234,17 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		})?;
 
 	if config.offchain_worker.enabled {
+		// The `--dev` chain lists the dev root account as a rarity committer, so give its offchain
+		// workers that key to sign rarity roots with. Other chains, the kitty presets included,
+		// need a rarity key inserted on purpose.
+		if config.chain_spec.id() == "dev" {
+			sp_keystore::SyncCryptoStore::sr25519_generate_new(
+				&*keystore_container.sync_keystore(),
+				node_template_runtime::pallet_kitties::KEY_TYPE,
+				Some("//Alice"),
+			)
+			.map_err(|e| ServiceError::Other(format!("Failed to insert the rarity key: {}", e)))?;
+		}
 		sc_service::build_offchain_workers(
 			&config,
 			task_manager.spawn_handle(),
@@ -216,16 +257,29 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let force_authoring = config.force_authoring;
 	let backoff_authoring_blocks: Option<()> = None;
 	let name = config.network.node_name.clone();
-	let enable_grandpa = !config.disable_grandpa;
+	let enable_grandpa = !config.disable_grandpa && sealing.is_none();
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	// In manual mode blocks are only sealed when asked to through the `engine_*` RPCs.
+	let (command_sink, commands_stream) = match sealing {
+		Some(Sealing::Manual) => {
+			let (command_sink, commands_stream) = mpsc::channel(1024);
+			(Some(command_sink), Some(commands_stream))
+		},
+		_ => (None, None),
+	};
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
 
 		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				command_sink: command_sink.clone(),
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
@@ -245,7 +299,93 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		telemetry: telemetry.as_mut(),
 	})?;
 
-	if role.is_authority() {
+	if let Some(sealing) = sealing {
+		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+			task_manager.spawn_handle(),
+			client.clone(),
+			transaction_pool.clone(),
+			prometheus_registry.as_ref(),
+			telemetry.as_ref().map(|x| x.handle()),
+		);
+
+		// The runtime checks that every block's Aura slot matches its timestamp, so instead of
+		// following the clock each block is stamped exactly one slot after the previous one.
+		// Sealing starts from the clock, or right after the best block when a chain restarted
+		// with a database is ahead of it.
+		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+		let slot_millis = slot_duration.as_millis();
+		let best_header = client
+			.header(client.info().best_hash)?
+			.ok_or_else(|| ServiceError::Other("Best block header is missing".into()))?;
+		let best_slot = sc_consensus_aura::find_pre_digest::<Block, AuraSignature>(&best_header)
+			.map_err(|e| ServiceError::Other(format!("Invalid best block digest: {}", e)))?;
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.expect("Current time is after the unix epoch; qed")
+			.as_millis() as u64;
+		let after_best = (*best_slot + 1) * slot_millis;
+		let next_timestamp = Arc::new(AtomicU64::new((now - now % slot_millis).max(after_best)));
+		let create_inherent_data_providers = move |_, ()| {
+			let next_timestamp = next_timestamp.clone();
+			async move {
+				let timestamp = sp_timestamp::InherentDataProvider::new(
+					next_timestamp.fetch_add(slot_millis, Ordering::SeqCst).into(),
+				);
+
+				let slot =
+					sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+						*timestamp,
+						slot_duration,
+					);
+
+				Ok((slot, timestamp))
+			}
+		};
+		let aura_data_provider = AuraConsensusDataProvider::new(client.clone());
+
+		let authorship_future = match sealing {
+			Sealing::Instant =>
+				sc_consensus_manual_seal::run_instant_seal_and_finalize(InstantSealParams {
+					block_import: client.clone(),
+					env: proposer_factory,
+					client,
+					pool: transaction_pool,
+					select_chain,
+					consensus_data_provider: Some(Box::new(aura_data_provider)),
+					create_inherent_data_providers,
+				})
+				.boxed(),
+			Sealing::Manual | Sealing::Interval(_) => {
+				let commands_stream: Pin<Box<dyn Stream<Item = EngineCommand<Hash>> + Send>> =
+					match (sealing, commands_stream) {
+						(Sealing::Interval(millis), _) => Box::pin(interval_commands(millis)),
+						(_, commands_stream) => Box::pin(
+							commands_stream.expect("Manual sealing has a command stream; qed"),
+						),
+					};
+
+				sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+					block_import: client.clone(),
+					env: proposer_factory,
+					client,
+					pool: transaction_pool,
+					commands_stream,
+					select_chain,
+					consensus_data_provider: Some(Box::new(aura_data_provider)),
+					create_inherent_data_providers,
+				})
+				.boxed()
+			},
+		};
+
+		// the authoring task is considered essential, i.e. if it
+		// fails we take down the service with it.
+		task_manager.spawn_essential_handle().spawn_blocking(
+			"manual-seal",
+			Some("block-authoring"),
+			authorship_future,
+		);
+	} else if role.is_authority() {
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
 			client.clone(),
@@ -340,3 +480,17 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	network_starter.start_network();
 	Ok(task_manager)
 }
+
+/// Commands sealing and finalizing a new block every `millis` milliseconds.
+fn interval_commands(millis: u64) -> impl Stream<Item = EngineCommand<Hash>> {
+	stream::unfold((), move |()| async move {
+		futures_timer::Delay::new(Duration::from_millis(millis)).await;
+		let command = EngineCommand::SealNewBlock {
+			create_empty: true,
+			finalize: true,
+			parent_hash: None,
+			sender: None,
+		};
+		Some((command, ()))
+	})
+}
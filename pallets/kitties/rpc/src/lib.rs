@@ -0,0 +1,176 @@
+//! RPC interface for the kitties pallet.
+//!
+//! - `kitties_familyTree(kitty_id, depth?, at?)`: the ancestors and descendants of a kitty.
+//! - `kitties_commonAncestor(first, second, at?)`: the nearest kitty two kitties descend from.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+pub use pallet_kitties_runtime_api::KittyFamilyApi as KittyFamilyRuntimeApi;
+use pallet_kitties_runtime_api::KittyId;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+
+/// The generations a family tree reaches when no depth is given.
+const DEFAULT_DEPTH: u32 = 4;
+
+#[rpc(client, server)]
+pub trait KittiesApi<BlockHash> {
+	/// The ancestors and descendants of a kitty up to `depth` generations, 4 by default, at the
+	/// best block or `at`. The runtime caps the depth at 32 generations and the tree at 512
+	/// relatives, setting `truncated` when relatives were left out.
+	#[method(name = "kitties_familyTree")]
+	fn family_tree(
+		&self,
+		kitty_id: KittyId,
+		depth: Option<u32>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<FamilyTree>>;
+
+	/// The nearest kitty both kitties descend from, at the best block or `at`. A kitty counts
+	/// among its own ancestors, and ancestors over 32 generations away are not searched.
+	#[method(name = "kitties_commonAncestor")]
+	fn common_ancestor(
+		&self,
+		first: KittyId,
+		second: KittyId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<CommonAncestor>>;
+}
+
+/// A kitty in the family tree of another.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Relative {
+	pub kitty_id: KittyId,
+	pub generation: u32,
+	pub parents: Option<(KittyId, KittyId)>,
+}
+
+/// The ancestors and descendants of a kitty, nearest generation first.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FamilyTree {
+	pub kitty_id: KittyId,
+	pub parents: Option<(KittyId, KittyId)>,
+	pub ancestors: Vec<Relative>,
+	pub descendants: Vec<Relative>,
+	pub truncated: bool,
+}
+
+/// The nearest common ancestor of two kitties.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommonAncestor {
+	pub kitty_id: KittyId,
+	pub generations_from_first: u32,
+	pub generations_from_second: u32,
+}
+
+impl From<pallet_kitties_runtime_api::Relative> for Relative {
+	fn from(relative: pallet_kitties_runtime_api::Relative) -> Self {
+		let pallet_kitties_runtime_api::Relative { kitty_id, generation, parents } = relative;
+		Relative { kitty_id, generation, parents }
+	}
+}
+
+impl From<pallet_kitties_runtime_api::FamilyTree> for FamilyTree {
+	fn from(tree: pallet_kitties_runtime_api::FamilyTree) -> Self {
+		FamilyTree {
+			kitty_id: tree.kitty_id,
+			parents: tree.parents,
+			ancestors: tree.ancestors.into_iter().map(Into::into).collect(),
+			descendants: tree.descendants.into_iter().map(Into::into).collect(),
+			truncated: tree.truncated,
+		}
+	}
+}
+
+impl From<pallet_kitties_runtime_api::CommonAncestor> for CommonAncestor {
+	fn from(common: pallet_kitties_runtime_api::CommonAncestor) -> Self {
+		let pallet_kitties_runtime_api::CommonAncestor {
+			kitty_id,
+			generations_from_first,
+			generations_from_second,
+		} = common;
+		CommonAncestor { kitty_id, generations_from_first, generations_from_second }
+	}
+}
+
+/// Answers the kitties RPC from the runtime API of `client`.
+pub struct Kitties<C, P> {
+	client: Arc<C>,
+	_marker: PhantomData<P>,
+}
+
+impl<C, P> Kitties<C, P> {
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error codes of the kitties RPC.
+pub enum Error {
+	/// The runtime API call failed.
+	RuntimeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+impl<C, Block> KittiesApiServer<<Block as BlockT>::Hash> for Kitties<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: KittyFamilyRuntimeApi<Block>,
+{
+	fn family_tree(
+		&self,
+		kitty_id: KittyId,
+		depth: Option<u32>,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<FamilyTree>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let tree = self
+			.client
+			.runtime_api()
+			.family_tree(at, kitty_id, depth.unwrap_or(DEFAULT_DEPTH))
+			.map_err(|e| runtime_error("Unable to query the family tree.", e))?;
+		Ok(tree.map(Into::into))
+	}
+
+	fn common_ancestor(
+		&self,
+		first: KittyId,
+		second: KittyId,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<CommonAncestor>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let common = self
+			.client
+			.runtime_api()
+			.common_ancestor(at, first, second)
+			.map_err(|e| runtime_error("Unable to query the common ancestor.", e))?;
+		Ok(common.map(Into::into))
+	}
+}
+
+fn runtime_error(message: &str, error: sp_api::ApiError) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(
+		Error::RuntimeError.into(),
+		message,
+		Some(error.to_string()),
+	))
+	.into()
+}
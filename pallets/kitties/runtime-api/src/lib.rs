@@ -0,0 +1,36 @@
+//! Runtime API definition for the kitties pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_kitties::{
+	CommonAncestor, FamilyTree, KittyId, KittyInfo, Relative, MAX_FAMILY_DEPTH, MAX_FAMILY_NODES,
+};
+
+sp_api::decl_runtime_apis! {
+	/// Read-only queries over the kitties pallet, for wallets and other clients.
+	pub trait KittiesApi<AccountId, Balance> where
+		AccountId: Codec,
+		Balance: Codec,
+	{
+		/// Everything known about a kitty, including its metadata and attributes.
+		fn kitty(kitty_id: KittyId) -> Option<KittyInfo<AccountId>>;
+		/// Every kitty has an id below this, though some of those may have been burned.
+		fn next_kitty_id() -> KittyId;
+		/// The number of kitties that can still be created before `MaxSupply` is reached.
+		fn remaining_supply() -> u32;
+		/// The price the next minted kitty will cost.
+		fn mint_price() -> Balance;
+		/// The price the next bred kitty will cost.
+		fn breed_price() -> Balance;
+	}
+
+	/// Queries over the family trees of kitties, for breeding UIs.
+	pub trait KittyFamilyApi {
+		/// The ancestors and descendants of a kitty up to `depth` generations, bounded by
+		/// `MAX_FAMILY_DEPTH` and `MAX_FAMILY_NODES`.
+		fn family_tree(kitty_id: KittyId, depth: u32) -> Option<FamilyTree>;
+		/// The nearest kitty both kitties descend from, counting the kitties themselves.
+		fn common_ancestor(first: KittyId, second: KittyId) -> Option<CommonAncestor>;
+	}
+}
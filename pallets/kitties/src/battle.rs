@@ -0,0 +1,54 @@
+//! Battle stats derived from kitty DNA and the pure resolution of a battle between two kitties.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use core::cmp::Ordering;
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// The fighting stats of a kitty, read from the last six bytes of its DNA.
+#[derive(Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub struct Stats {
+	pub attack: u32,
+	pub defense: u32,
+	pub speed: u32,
+}
+
+impl Stats {
+	pub fn from_dna(dna: &[u8; 16]) -> Self {
+		let stat = |i: usize| 1 + u32::from(dna[i]) + u32::from(dna[i + 1]);
+		Stats { attack: stat(10), defense: stat(12), speed: stat(14) }
+	}
+
+	fn power(&self) -> u64 {
+		2 * u64::from(self.attack) + u64::from(self.defense) + u64::from(self.speed)
+	}
+}
+
+/// The side that won a battle.
+#[derive(Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum Winner {
+	Challenger,
+	Opponent,
+}
+
+/// The number of battles a kitty won and lost.
+#[derive(
+	Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, Default, TypeInfo, MaxEncodedLen,
+)]
+pub struct BattleRecord {
+	pub wins: u32,
+	pub losses: u32,
+}
+
+/// Resolve a battle. Each side's power is scaled by a luck roll between 128 and 383 taken from
+/// `random`, so a weaker kitty can win against one up to three times as strong. On a tie the
+/// faster kitty wins, and the opponent when both are as fast.
+pub fn resolve(challenger: &Stats, opponent: &Stats, random: [u8; 16]) -> Winner {
+	let roll = |stats: &Stats, luck: u8| stats.power() * (128 + u64::from(luck));
+	match roll(challenger, random[0]).cmp(&roll(opponent, random[1])) {
+		Ordering::Greater => Winner::Challenger,
+		Ordering::Less => Winner::Opponent,
+		Ordering::Equal if challenger.speed > opponent.speed => Winner::Challenger,
+		Ordering::Equal => Winner::Opponent,
+	}
+}
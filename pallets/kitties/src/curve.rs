@@ -0,0 +1,44 @@
+//! Bonding curves pricing newly created kitties from the number of kitties created so far.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{AtLeast32BitUnsigned, Saturating},
+	FixedPointNumber, FixedPointOperand, FixedU128, Perbill, RuntimeDebug,
+};
+
+/// Curve parameters that can be tuned at runtime by `Config::ForceOrigin`.
+#[derive(
+	Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, Default, TypeInfo, MaxEncodedLen,
+)]
+pub struct CurveParams {
+	/// How steeply the price grows with supply, relative to the base price. With the default
+	/// of zero every curve charges the base price.
+	pub rate: Perbill,
+}
+
+/// Prices a kitty from a base price and the current supply.
+pub trait PriceCurve<Balance> {
+	fn price(base: Balance, supply: u32, params: &CurveParams) -> Balance;
+}
+
+/// `base + base * rate * supply`
+pub struct LinearCurve;
+
+impl<Balance: AtLeast32BitUnsigned + Copy> PriceCurve<Balance> for LinearCurve {
+	fn price(base: Balance, supply: u32, params: &CurveParams) -> Balance {
+		base.saturating_add((params.rate * base).saturating_mul(supply.into()))
+	}
+}
+
+/// `base * (1 + rate) ^ supply`
+pub struct ExponentialCurve;
+
+impl<Balance: AtLeast32BitUnsigned + FixedPointOperand> PriceCurve<Balance> for ExponentialCurve {
+	fn price(base: Balance, supply: u32, params: &CurveParams) -> Balance {
+		FixedU128::from(params.rate)
+			.saturating_add(FixedU128::one())
+			.saturating_pow(supply as usize)
+			.saturating_mul_int(base)
+	}
+}
@@ -0,0 +1,154 @@
+//! Family trees of kitties, walked through the parents and children indices.
+
+use crate::KittyId;
+use codec::{Decode, Encode};
+use frame_support::sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet, vec_deque::VecDeque},
+	vec::Vec,
+};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// The most generations a family tree reaches in either direction.
+pub const MAX_FAMILY_DEPTH: u32 = 32;
+
+/// The most relatives a family tree holds, or a common ancestor search visits per kitty.
+pub const MAX_FAMILY_NODES: u32 = 512;
+
+/// A kitty in the family tree of another.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
+pub struct Relative {
+	pub kitty_id: KittyId,
+	/// How many generations the relative is from the kitty the tree is of.
+	pub generation: u32,
+	/// The parents of the relative, which may lie beyond the tree. Burned kitties have none.
+	pub parents: Option<(KittyId, KittyId)>,
+}
+
+/// The ancestors and descendants of a kitty, up to a number of generations.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
+pub struct FamilyTree {
+	pub kitty_id: KittyId,
+	pub parents: Option<(KittyId, KittyId)>,
+	/// Nearest generation first, and by kitty id within a generation.
+	pub ancestors: Vec<Relative>,
+	/// Nearest generation first, and by kitty id within a generation.
+	pub descendants: Vec<Relative>,
+	/// Whether relatives within reach were left out to stay within `MAX_FAMILY_NODES`.
+	pub truncated: bool,
+}
+
+/// The nearest kitty two kitties both descend from.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
+pub struct CommonAncestor {
+	pub kitty_id: KittyId,
+	/// Generations between the ancestor and the first kitty, 0 if it is the first kitty.
+	pub generations_from_first: u32,
+	/// Generations between the ancestor and the second kitty, 0 if it is the second kitty.
+	pub generations_from_second: u32,
+}
+
+/// The family tree of `kitty_id` up to `depth` generations, capped at `MAX_FAMILY_DEPTH`.
+/// Ancestors are visited first and share the `MAX_FAMILY_NODES` budget with descendants.
+pub fn walk_family_tree<I: Iterator<Item = KittyId>>(
+	kitty_id: KittyId,
+	depth: u32,
+	parents_of: impl Fn(KittyId) -> Option<(KittyId, KittyId)>,
+	children_of: impl Fn(KittyId) -> I,
+) -> FamilyTree {
+	let depth = depth.min(MAX_FAMILY_DEPTH);
+	let mut budget = MAX_FAMILY_NODES;
+	let (ancestors, ancestors_truncated) = walk(kitty_id, depth, &mut budget, |id| {
+		parents_of(id).into_iter().flat_map(|(p1, p2)| [p1, p2])
+	});
+	let (descendants, descendants_truncated) = walk(kitty_id, depth, &mut budget, children_of);
+
+	let relatives = |found: Vec<(KittyId, u32)>| {
+		let mut relatives: Vec<Relative> = found
+			.into_iter()
+			.map(|(kitty_id, generation)| Relative {
+				kitty_id,
+				generation,
+				parents: parents_of(kitty_id),
+			})
+			.collect();
+		relatives.sort_unstable_by_key(|relative| (relative.generation, relative.kitty_id));
+		relatives
+	};
+	FamilyTree {
+		kitty_id,
+		parents: parents_of(kitty_id),
+		ancestors: relatives(ancestors),
+		descendants: relatives(descendants),
+		truncated: ancestors_truncated || descendants_truncated,
+	}
+}
+
+/// The common ancestor of `first` and `second` with the fewest generations between them, the
+/// lowest kitty id breaking ties. A kitty counts among its own ancestors, so a kitty is the
+/// common ancestor of itself and any of its descendants.
+pub fn find_common_ancestor(
+	first: KittyId,
+	second: KittyId,
+	parents_of: impl Fn(KittyId) -> Option<(KittyId, KittyId)>,
+) -> Option<CommonAncestor> {
+	let ancestry = |kitty_id| {
+		let mut budget = MAX_FAMILY_NODES;
+		let (ancestors, _) = walk(kitty_id, MAX_FAMILY_DEPTH, &mut budget, |id| {
+			parents_of(id).into_iter().flat_map(|(p1, p2)| [p1, p2])
+		});
+		let mut ancestry: BTreeMap<KittyId, u32> = ancestors.into_iter().collect();
+		ancestry.insert(kitty_id, 0);
+		ancestry
+	};
+
+	let second_ancestry = ancestry(second);
+	ancestry(first)
+		.into_iter()
+		.filter_map(|(kitty_id, generations_from_first)| {
+			Some(CommonAncestor {
+				kitty_id,
+				generations_from_first,
+				generations_from_second: *second_ancestry.get(&kitty_id)?,
+			})
+		})
+		.min_by_key(|common| {
+			(common.generations_from_first + common.generations_from_second, common.kitty_id)
+		})
+}
+
+/// Walk breadth-first from `kitty_id` to the kitties `next` leads to, up to `depth`
+/// generations, visiting every relative once at its nearest generation and spending one unit of
+/// `budget` on each. Returns the relatives with their generation, and whether the budget ran out
+/// before all of them were visited.
+fn walk<I: Iterator<Item = KittyId>>(
+	kitty_id: KittyId,
+	depth: u32,
+	budget: &mut u32,
+	next: impl Fn(KittyId) -> I,
+) -> (Vec<(KittyId, u32)>, bool) {
+	let mut visited = BTreeSet::new();
+	visited.insert(kitty_id);
+	let mut relatives = Vec::new();
+	let mut queue = VecDeque::new();
+	queue.push_back((kitty_id, 0));
+
+	while let Some((id, generation)) = queue.pop_front() {
+		if generation == depth {
+			continue
+		}
+		for relative in next(id) {
+			if visited.contains(&relative) {
+				continue
+			}
+			if *budget == 0 {
+				return (relatives, true)
+			}
+			*budget -= 1;
+			visited.insert(relative);
+			relatives.push((relative, generation + 1));
+			queue.push_back((relative, generation + 1));
+		}
+	}
+	(relatives, false)
+}
@@ -1,8 +1,49 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub use battle::{resolve, BattleRecord, Stats, Winner};
+pub use curve::{CurveParams, ExponentialCurve, LinearCurve, PriceCurve};
+pub use family::{
+	find_common_ancestor, walk_family_tree, CommonAncestor, FamilyTree, Relative, MAX_FAMILY_DEPTH,
+	MAX_FAMILY_NODES,
+};
 pub use pallet::*;
+pub use progress::{inherit_dna, level_for, Progress};
+pub use rarity::{merkle_root, rarity_leaf, rarity_scores, Traits};
 
+mod battle;
+mod curve;
+mod family;
 mod migrations;
+mod progress;
+mod rarity;
+
+use sp_runtime::KeyTypeId;
+
+/// The key type of the keys the offchain worker signs rarity roots with.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"kitr");
+
+/// The offchain storage key of the latest rarity scores, kept by the offchain worker as a
+/// SCALE-encoded `(BlockNumber, Vec<(KittyId, u64)>)` in kitty id order.
+pub const RARITY_SCORES_KEY: &[u8] = b"kitties::rarity-scores";
+
+/// The `sr25519` keys of [`KEY_TYPE`] that rarity roots are signed with.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		MultiSignature, MultiSigner,
+	};
+	app_crypto!(sr25519, KEY_TYPE);
+
+	/// Signs rarity roots in runtimes using `MultiSignature`.
+	pub struct RarityAuthId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for RarityAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericPublic = sr25519::Public;
+		type GenericSignature = sr25519::Signature;
+	}
+}
 
 #[cfg(test)]
 mod mock;
@@ -15,17 +56,56 @@ pub mod pallet {
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
 
+	use crate::{
+		find_common_ancestor, inherit_dna, level_for, merkle_root, migrations, rarity_leaf,
+		rarity_scores, resolve, walk_family_tree, BattleRecord, CommonAncestor, CurveParams,
+		FamilyTree, PriceCurve, Progress, Stats, Winner, RARITY_SCORES_KEY,
+	};
 	use frame_support::{
-		traits::{Currency, ExistenceRequirement, Randomness},
+		dispatch::{DispatchErrorWithPostInfo, Pays},
+		sp_std::vec::Vec,
+		traits::{
+			BalanceStatus, Currency, ExistenceRequirement, GenesisBuild, Randomness,
+			ReservableCurrency,
+		},
 		PalletId,
 	};
+	use frame_system::offchain::{
+		AppCrypto, SendTransactionTypes, SendUnsignedTransaction, SignedPayload, Signer,
+		SigningTypes,
+	};
 	use sp_io::hashing::blake2_128;
-	use sp_runtime::traits::AccountIdConversion;
-	use crate::migrations;
+	use sp_runtime::{
+		offchain::storage::StorageValueRef,
+		traits::{
+			AccountIdConversion, IdentifyAccount, One, Saturating, UniqueSaturatedInto, Zero,
+		},
+	};
 
 	pub type KittyId = u32;
+	pub type SwapId = u32;
+	pub type ChallengeId = u32;
+	pub type Shares = u64;
+	pub type CollectionId = u32;
 	pub type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub type SwapOf<T> = Swap<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+	pub type CollectionOf<T> = Collection<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		BoundedVec<u8, <T as Config>::MaxCollectionMetadataLen>,
+	>;
+	pub type LoanOf<T> =
+		Loan<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
+	pub type ChallengeOf<T> = Challenge<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		<T as frame_system::Config>::BlockNumber,
+	>;
+	pub type DepositOf<T> = Deposit<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+	pub type MetadataOf<T> = BoundedVec<u8, <T as Config>::MaxMetadataLen>;
+	pub type AttributeKeyOf<T> = BoundedVec<u8, <T as Config>::MaxAttributeKeyLen>;
+	pub type AttributeValueOf<T> = BoundedVec<u8, <T as Config>::MaxAttributeValueLen>;
 
 	#[derive(
 		Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, Default, TypeInfo, MaxEncodedLen,
@@ -35,7 +115,113 @@ pub mod pallet {
 		pub dna: [u8; 16],
 		pub name: [u8; 8],
 	}
-	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
+	/// An open offer to exchange `offered` (owned by `proposer`) for `wanted`, optionally
+	/// sweetened with a balance paid by the proposer when the swap is accepted.
+	#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub struct Swap<AccountId, Balance> {
+		pub proposer: AccountId,
+		pub offered: KittyId,
+		pub wanted: KittyId,
+		pub top_up: Option<Balance>,
+		/// Reserved from the proposer until the swap is accepted, cancelled or invalidated.
+		pub deposit: Balance,
+	}
+
+	/// Usage rights over a kitty held by `borrower` until block `until`, when the loan is
+	/// cleared automatically.
+	#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub struct Loan<AccountId, BlockNumber> {
+		pub borrower: AccountId,
+		pub until: BlockNumber,
+	}
+
+	/// An open challenge of kitty `opponent` by `challenger` with kitty `kitty`. Both sides put
+	/// up `stake`, which goes to the winner. It can be accepted until block `expires`.
+	#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub struct Challenge<AccountId, Balance, BlockNumber> {
+		pub challenger: AccountId,
+		pub kitty: KittyId,
+		pub opponent: KittyId,
+		pub stake: Balance,
+		pub expires: BlockNumber,
+	}
+
+	/// Who put a lock on a kitty, which decides who may lift it.
+	#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub enum LockReason {
+		/// Locked by its owner, e.g. while it is used in a game.
+		Owner,
+		/// Frozen by `Config::FreezeOrigin`, e.g. in response to a theft report.
+		Frozen,
+	}
+
+	/// The collection of kitties minted with `create`, governed by the pallet configuration.
+	/// It holds every kitty created before collections were introduced.
+	pub const DEFAULT_COLLECTION: CollectionId = 0;
+
+	/// A collection of kitties created by `owner`, who receives `mint_price` for every kitty
+	/// minted into it. Kitties can only be bred with kitties of the same collection.
+	#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub struct Collection<AccountId, Balance, Metadata> {
+		pub owner: AccountId,
+		pub metadata: Metadata,
+		pub mint_price: Balance,
+		/// The maximum number of kitties that can be minted or bred into the collection.
+		pub max_supply: u32,
+		/// The number of kitties minted or bred into the collection so far.
+		pub supply: u32,
+	}
+
+	/// A storage deposit reserved from `depositor`, returned to it when the data it pays for is
+	/// replaced or cleared, even if the kitty has changed hands since.
+	#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub struct Deposit<AccountId, Balance> {
+		pub depositor: AccountId,
+		pub amount: Balance,
+	}
+
+	/// Everything known about a kitty, as returned by the runtime API.
+	#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
+	pub struct KittyInfo<AccountId> {
+		pub owner: AccountId,
+		pub kitty: Kitty,
+		pub parents: Option<(KittyId, KittyId)>,
+		pub collection_id: CollectionId,
+		pub progress: Progress,
+		pub record: BattleRecord,
+		pub on_sale: bool,
+		/// The off-chain metadata URI or content hash, empty if unset.
+		pub metadata: Vec<u8>,
+		pub attributes: Vec<(Vec<u8>, Vec<u8>)>,
+	}
+
+	/// A rarity root proposed by the offchain worker, signed with the key of `public`.
+	#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
+	pub struct RarityPayload<Public, BlockNumber> {
+		/// The block whose kitties were scored.
+		pub block_number: BlockNumber,
+		/// The Merkle root over the `rarity_leaf` of every kitty, in kitty id order.
+		pub root: [u8; 32],
+		pub kitty_count: u32,
+		pub public: Public,
+	}
+
+	impl<T: SigningTypes> SignedPayload<T> for RarityPayload<T::Public, T::BlockNumber> {
+		fn public(&self) -> T::Public {
+			self.public.clone()
+		}
+	}
+
+	/// The rarity scores of `kitty_count` kitties at `block_number`, committed to by `root`.
+	#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub struct RarityCommitment<BlockNumber> {
+		pub block_number: BlockNumber,
+		pub root: [u8; 32],
+		pub kitty_count: u32,
+	}
+
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(5);
 
 	#[pallet::pallet]
 	#[pallet::storage_version(STORAGE_VERSION)]
@@ -43,13 +229,90 @@ pub mod pallet {
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config:
+		frame_system::Config + SendTransactionTypes<Call<Self>> + SigningTypes
+	{
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
-		type Currency: Currency<Self::AccountId>;
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// The price buyers pay for listed kitties.
 		#[pallet::constant]
 		type KittyPrice: Get<BalanceOf<Self>>;
+		/// The base price of minting a kitty, before `PriceCurve` is applied.
+		#[pallet::constant]
+		type MintPrice: Get<BalanceOf<Self>>;
+		/// The base price of breeding a kitty, before `PriceCurve` is applied.
+		#[pallet::constant]
+		type BreedPrice: Get<BalanceOf<Self>>;
+		/// The curve the mint and breed prices follow as supply grows.
+		type PriceCurve: PriceCurve<BalanceOf<Self>>;
 		type PalletId: Get<PalletId>;
+		/// The maximum number of open swaps a kitty can be offered or wanted in.
+		#[pallet::constant]
+		type MaxSwapsPerKitty: Get<u32>;
+		/// The deposit reserved from the proposer of a swap while it is open.
+		#[pallet::constant]
+		type SwapDeposit: Get<BalanceOf<Self>>;
+		/// The maximum number of loans that can end in the same block.
+		#[pallet::constant]
+		type MaxLoansPerBlock: Get<u32>;
+		/// The origin allowed to freeze any kitty and to lift any lock, e.g. root or a
+		/// governance collective. Owners lock and unlock their own kitties with a signed origin.
+		type FreezeOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		/// The origin allowed to resolve disputes by moving, burning, editing or delisting any
+		/// kitty, e.g. root.
+		type ForceOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		/// The maximum number of kitties that can ever be created, by minting or breeding.
+		#[pallet::constant]
+		type MaxSupply: Get<u32>;
+		/// How many kitties a single account can mint within one `MintPeriod`.
+		#[pallet::constant]
+		type MaxMintsPerAccountPerPeriod: Get<u32>;
+		/// The length in blocks of the periods over which per-account mints are counted.
+		#[pallet::constant]
+		type MintPeriod: Get<Self::BlockNumber>;
+		/// The maximum length of the metadata describing a collection.
+		#[pallet::constant]
+		type MaxCollectionMetadataLen: Get<u32>;
+		/// The maximum length of a kitty's metadata, e.g. an IPFS CID or a URI.
+		#[pallet::constant]
+		type MaxMetadataLen: Get<u32>;
+		#[pallet::constant]
+		type MaxAttributeKeyLen: Get<u32>;
+		#[pallet::constant]
+		type MaxAttributeValueLen: Get<u32>;
+		/// The maximum number of attributes a kitty can have set at once.
+		#[pallet::constant]
+		type MaxAttributesPerKitty: Get<u32>;
+		/// The deposit reserved for every byte of kitty metadata and attributes stored.
+		#[pallet::constant]
+		type DepositPerByte: Get<BalanceOf<Self>>;
+		/// The fee paid for feeding a kitty.
+		#[pallet::constant]
+		type FeedFee: Get<BalanceOf<Self>>;
+		/// The experience a kitty gains from being fed.
+		#[pallet::constant]
+		type FeedExperience: Get<u32>;
+		/// The experience each parent gains from breeding.
+		#[pallet::constant]
+		type BreedExperience: Get<u32>;
+		/// The experience needed to reach levels 2, 3 and so on, in ascending order.
+		#[pallet::constant]
+		type LevelThresholds: Get<Vec<u32>>;
+		/// The number of blocks a challenge can be accepted for.
+		#[pallet::constant]
+		type ChallengePeriod: Get<Self::BlockNumber>;
+		/// The maximum number of battles that can be fought in the same block.
+		#[pallet::constant]
+		type MaxBattlesPerBlock: Get<u32>;
+		/// The keys the offchain worker signs rarity roots with.
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+		/// The number of blocks between rarity roots.
+		#[pallet::constant]
+		type RarityInterval: Get<Self::BlockNumber>;
+		/// The priority of the unsigned transactions submitting rarity roots.
+		#[pallet::constant]
+		type RarityPriority: Get<TransactionPriority>;
 	}
 
 	// The pallet's runtime storage items.
@@ -70,19 +333,355 @@ pub mod pallet {
 	pub type KittyParents<T: Config> =
 		StorageMap<_, Blake2_128Concat, KittyId, (KittyId, KittyId), OptionQuery>;
 
+	/// The children of a kitty, the reverse of `KittyParents`.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_children)]
+	pub type KittyChildren<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, KittyId, Blake2_128Concat, KittyId, ()>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn kitty_on_sale)]
 	pub type KittyOnSale<T: Config> = StorageMap<_, Blake2_128Concat, KittyId, ()>;
 
+	#[pallet::type_value]
+	pub fn DefaultNextCollectionId() -> CollectionId {
+		DEFAULT_COLLECTION + 1
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_collection_id)]
+	pub type NextCollectionId<T> =
+		StorageValue<_, CollectionId, ValueQuery, DefaultNextCollectionId>;
+
+	/// Collections created with `create_collection`. `DEFAULT_COLLECTION` has no entry.
+	#[pallet::storage]
+	#[pallet::getter(fn collections)]
+	pub type Collections<T: Config> =
+		StorageMap<_, Blake2_128Concat, CollectionId, CollectionOf<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_collection)]
+	pub type KittyCollection<T> =
+		StorageMap<_, Blake2_128Concat, KittyId, CollectionId, ValueQuery>;
+
+	/// The kitties of each collection, keyed by `(CollectionId, KittyId)`.
+	#[pallet::storage]
+	pub type CollectionKitties<T> =
+		StorageDoubleMap<_, Blake2_128Concat, CollectionId, Blake2_128Concat, KittyId, ()>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_swap_id)]
+	pub type NextSwapId<T> = StorageValue<_, SwapId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn swaps)]
+	pub type Swaps<T: Config> = StorageMap<_, Blake2_128Concat, SwapId, SwapOf<T>>;
+
+	/// Index of the open swaps each kitty takes part in, either as offered or wanted kitty.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_swaps)]
+	pub type KittySwaps<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		KittyId,
+		BoundedVec<SwapId, T::MaxSwapsPerKitty>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_loans)]
+	pub type KittyLoans<T: Config> = StorageMap<_, Blake2_128Concat, KittyId, LoanOf<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_locks)]
+	pub type KittyLocks<T: Config> = StorageMap<_, Blake2_128Concat, KittyId, LockReason>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_progress)]
+	pub type KittyProgress<T> = StorageMap<_, Blake2_128Concat, KittyId, Progress>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_challenge_id)]
+	pub type NextChallengeId<T> = StorageValue<_, ChallengeId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn challenges)]
+	pub type Challenges<T: Config> = StorageMap<_, Blake2_128Concat, ChallengeId, ChallengeOf<T>>;
+
+	/// The account that accepted a challenge, whose battle is waiting to be fought.
+	#[pallet::storage]
+	#[pallet::getter(fn challenge_acceptor)]
+	pub type ChallengeAcceptors<T: Config> =
+		StorageMap<_, Blake2_128Concat, ChallengeId, T::AccountId>;
+
+	/// The accepted challenges fought at the start of each block.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_battles)]
+	pub type PendingBattles<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::BlockNumber,
+		BoundedVec<ChallengeId, T::MaxBattlesPerBlock>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn battle_records)]
+	pub type BattleRecords<T> = StorageMap<_, Blake2_128Concat, KittyId, BattleRecord, ValueQuery>;
+
+	/// The total number of shares issued for each fractionalized kitty, which is held by the
+	/// pallet account until it is redeemed.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_fractions)]
+	pub type KittyFractions<T> = StorageMap<_, Blake2_128Concat, KittyId, Shares>;
+
+	/// The shares of each fractionalized kitty held by each account.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_shares)]
+	pub type KittyShares<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		KittyId,
+		Blake2_128Concat,
+		T::AccountId,
+		Shares,
+		ValueQuery,
+	>;
+
+	/// Off-chain metadata set by a kitty's owner and the deposit paid for it.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_metadata)]
+	pub type KittyMetadata<T: Config> =
+		StorageMap<_, Blake2_128Concat, KittyId, (MetadataOf<T>, DepositOf<T>)>;
+
+	/// Key/value attributes set by a kitty's owner and the deposit paid for each.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_attributes)]
+	pub type KittyAttributes<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		KittyId,
+		Blake2_128Concat,
+		AttributeKeyOf<T>,
+		(AttributeValueOf<T>, DepositOf<T>),
+	>;
+
+	/// The number of attributes set on each kitty, bounded by `MaxAttributesPerKitty`.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_attribute_count)]
+	pub type KittyAttributeCount<T: Config> =
+		StorageMap<_, Blake2_128Concat, KittyId, u32, ValueQuery>;
+
+	/// The blocks at which minting opens and closes; an unset bound leaves that side open.
+	#[pallet::storage]
+	#[pallet::getter(fn mint_window)]
+	pub type MintWindow<T: Config> =
+		StorageValue<_, (Option<T::BlockNumber>, Option<T::BlockNumber>), ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn price_curve_params)]
+	pub type PriceCurveParams<T> = StorageValue<_, CurveParams, ValueQuery>;
+
+	/// The mint period an account last minted in and how many kitties it minted during it.
+	#[pallet::storage]
+	#[pallet::getter(fn account_mints)]
+	pub type AccountMints<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (T::BlockNumber, u32), ValueQuery>;
+
+	/// Kitties whose loan ends at a given block, cleared in `on_initialize`.
+	#[pallet::storage]
+	pub type LoanExpiries<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		BoundedVec<KittyId, T::MaxLoansPerBlock>,
+		ValueQuery,
+	>;
+
+	/// The latest rarity root committed by an offchain worker.
+	#[pallet::storage]
+	#[pallet::getter(fn rarity_root)]
+	pub type RarityRoot<T: Config> = StorageValue<_, RarityCommitment<T::BlockNumber>>;
+
+	/// The first block whose rarity scores can be committed, `RarityInterval` blocks after the
+	/// scores of the latest root.
+	#[pallet::storage]
+	#[pallet::getter(fn next_rarity_at)]
+	pub type NextRarityAt<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+	/// Accounts whose keys may sign rarity roots.
+	#[pallet::storage]
+	#[pallet::getter(fn rarity_committer)]
+	pub type RarityCommitters<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Kitties created at genesis in the default collection, as `(owner, dna, name)`.
+		pub kitties: Vec<(T::AccountId, [u8; 16], [u8; 8])>,
+		/// Genesis kitties listed for sale.
+		pub on_sale: Vec<KittyId>,
+		/// Accounts whose keys may sign rarity roots.
+		pub rarity_committers: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { kitties: Vec::new(), on_sale: Vec::new(), rarity_committers: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (owner, dna, name) in &self.kitties {
+				assert!(
+					Pallet::<T>::next_kitty_id() < T::MaxSupply::get(),
+					"genesis kitties exceed MaxSupply"
+				);
+				let kitty_id = Pallet::<T>::get_next_id().expect("kitty ids below MaxSupply; qed");
+				Kitties::<T>::insert(kitty_id, Kitty { dna: *dna, name: *name });
+				KittyOwner::<T>::insert(kitty_id, owner);
+				Pallet::<T>::add_to_collection(DEFAULT_COLLECTION, kitty_id);
+				KittyProgress::<T>::insert(kitty_id, Progress::default());
+			}
+			for kitty_id in &self.on_sale {
+				assert!(Kitties::<T>::contains_key(kitty_id), "listed genesis kitty doesn't exist");
+				KittyOnSale::<T>::insert(kitty_id, ());
+			}
+			for who in &self.rarity_committers {
+				RarityCommitters::<T>::insert(who, ());
+			}
+		}
+	}
+
 	// Pallets use events to inform users when important changes are made.
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		KittyCreated { who: T::AccountId, kitty_id: KittyId, kitty: Kitty },
-		KittyBred { who: T::AccountId, kitty_id: KittyId, kitty: Kitty },
+		/// A new kitty was minted by `who` into a collection.
+		KittyCreated {
+			who: T::AccountId,
+			collection_id: CollectionId,
+			kitty_id: KittyId,
+			kitty: Kitty,
+		},
+		/// A new kitty was bred by `who` from the kitties in `parents`.
+		KittyBred {
+			who: T::AccountId,
+			kitty_id: KittyId,
+			kitty: Kitty,
+			parents: (KittyId, KittyId),
+		},
+		/// A kitty was given by `who` to `recipient`.
 		KittyTransferred { who: T::AccountId, recipient: T::AccountId, kitty_id: KittyId },
-		KittyOnSale { who: T::AccountId, kitty_id: KittyId },
-		KittyBought { who: T::AccountId, kitty_id: KittyId },
+		/// A kitty was listed by its owner and can be bought for `price`.
+		KittyOnSale { who: T::AccountId, kitty_id: KittyId, price: BalanceOf<T> },
+		/// A listed kitty was bought by `who` from `seller`, paying `price`.
+		KittyBought {
+			who: T::AccountId,
+			seller: T::AccountId,
+			kitty_id: KittyId,
+			price: BalanceOf<T>,
+		},
+		/// `who` offered to exchange kitty `offered` for kitty `wanted`, adding `top_up`.
+		SwapProposed {
+			who: T::AccountId,
+			swap_id: SwapId,
+			offered: KittyId,
+			wanted: KittyId,
+			top_up: Option<BalanceOf<T>>,
+		},
+		/// `who` accepted the swap offered by `proposer`; both kitties changed hands.
+		SwapAccepted { who: T::AccountId, proposer: T::AccountId, swap_id: SwapId },
+		/// The swap was withdrawn by its proposer.
+		SwapCancelled { who: T::AccountId, swap_id: SwapId },
+		/// The swap was dropped because one of its kitties changed hands.
+		SwapInvalidated { swap_id: SwapId },
+		/// `who` lent a kitty to `borrower` until block `until`.
+		KittyLent {
+			who: T::AccountId,
+			borrower: T::AccountId,
+			kitty_id: KittyId,
+			until: T::BlockNumber,
+		},
+		/// The loan of a kitty to `borrower` ended.
+		KittyLoanEnded { borrower: T::AccountId, kitty_id: KittyId },
+		/// A kitty was locked against transfer, sale and breeding.
+		KittyLocked { kitty_id: KittyId, reason: LockReason },
+		/// The lock on a kitty was lifted.
+		KittyUnlocked { kitty_id: KittyId },
+		/// `ForceOrigin` moved a kitty from `from` to `to`.
+		KittyForceTransferred { kitty_id: KittyId, from: T::AccountId, to: T::AccountId },
+		/// `ForceOrigin` destroyed a kitty owned by `owner`.
+		KittyBurned { kitty_id: KittyId, owner: T::AccountId },
+		/// `ForceOrigin` replaced the DNA of a kitty.
+		KittyDnaSet { kitty_id: KittyId, dna: [u8; 16] },
+		/// `ForceOrigin` removed a kitty from sale.
+		KittyDelisted { kitty_id: KittyId },
+		/// `ForceOrigin` changed the blocks between which minting is allowed.
+		MintWindowSet { open: Option<T::BlockNumber>, close: Option<T::BlockNumber> },
+		/// `ForceOrigin` changed the parameters of the mint and breed price curve.
+		PriceCurveSet { params: CurveParams },
+		/// `who` created a collection minting kitties for `mint_price`, up to `max_supply`.
+		CollectionCreated {
+			who: T::AccountId,
+			collection_id: CollectionId,
+			mint_price: BalanceOf<T>,
+			max_supply: u32,
+		},
+		/// The owner of a kitty set its metadata.
+		MetadataSet { kitty_id: KittyId, data: MetadataOf<T> },
+		/// The metadata of a kitty was cleared and its deposit returned.
+		MetadataCleared { kitty_id: KittyId },
+		/// The owner of a kitty set the attribute `key` to `value`.
+		AttributeSet { kitty_id: KittyId, key: AttributeKeyOf<T>, value: AttributeValueOf<T> },
+		/// An attribute of a kitty was cleared and its deposit returned.
+		AttributeCleared { kitty_id: KittyId, key: AttributeKeyOf<T> },
+		/// `who` fed a kitty, bringing its experience to `experience`.
+		KittyFed { who: T::AccountId, kitty_id: KittyId, experience: u32 },
+		/// A kitty reached a new level.
+		KittyLevelUp { kitty_id: KittyId, level: u32 },
+		/// `who` challenged kitty `opponent` with kitty `kitty_id` for `stake`.
+		ChallengeIssued {
+			who: T::AccountId,
+			challenge_id: ChallengeId,
+			kitty_id: KittyId,
+			opponent: KittyId,
+			stake: BalanceOf<T>,
+			expires: T::BlockNumber,
+		},
+		/// `who` accepted a challenge, which is fought at the start of block `battle_at`.
+		ChallengeAccepted {
+			who: T::AccountId,
+			challenge_id: ChallengeId,
+			battle_at: T::BlockNumber,
+		},
+		/// The battle of an accepted challenge was fought; `winner` took the stake of `loser`.
+		BattleResolved {
+			challenge_id: ChallengeId,
+			winner: KittyId,
+			loser: KittyId,
+			stake: BalanceOf<T>,
+		},
+		/// A challenge was withdrawn or expired, or a kitty of an accepted challenge was burned
+		/// before the battle, and the stakes were returned.
+		ChallengeRefunded { challenge_id: ChallengeId },
+		/// `who` locked a kitty in the pallet account in exchange for `shares` shares of it.
+		KittyFractionalized { who: T::AccountId, kitty_id: KittyId, shares: Shares },
+		/// Shares of a fractionalized kitty changed hands.
+		SharesTransferred {
+			kitty_id: KittyId,
+			from: T::AccountId,
+			to: T::AccountId,
+			amount: Shares,
+		},
+		/// `who` redeemed all shares of a kitty and took ownership of it.
+		KittyRedeemed { who: T::AccountId, kitty_id: KittyId },
+		/// The rarity scores of `kitty_count` kitties at `block_number` were committed.
+		RarityRootCommitted { block_number: T::BlockNumber, root: [u8; 32], kitty_count: u32 },
+		/// `ForceOrigin` allowed or disallowed `who` to sign rarity roots.
+		RarityCommitterSet { who: T::AccountId, allowed: bool },
 	}
 
 	// Errors inform users that something went wrong.
@@ -94,12 +693,81 @@ pub mod pallet {
 		AlreadyOnSale,
 		AlreadyOwned,
 		NotOnSale,
+		InvalidSwapId,
+		NotSwapProposer,
+		TooManySwaps,
+		KittyOnLoan,
+		InvalidLoanPeriod,
+		TooManyLoans,
+		KittyLocked,
+		NotLocked,
+		SupplyCapReached,
+		MintWindowClosed,
+		MintLimitReached,
+		InvalidCollectionId,
+		CollectionMismatch,
+		NoMetadata,
+		NoAttribute,
+		TooManyAttributes,
+		InvalidChallengeId,
+		ChallengeExpired,
+		ChallengeNotExpired,
+		ChallengeAccepted,
+		TooManyBattles,
+		KittyFractionalized,
+		NotFractionalized,
+		InvalidShares,
+		InsufficientShares,
+		NotRarityCommitter,
+		StaleRarityRoot,
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			let ended = LoanExpiries::<T>::take(n);
+			let count = ended.len() as u64;
+			for kitty_id in ended {
+				if let Some(loan) = KittyLoans::<T>::take(kitty_id) {
+					Self::deposit_event(Event::KittyLoanEnded {
+						borrower: loan.borrower,
+						kitty_id,
+					});
+				}
+			}
+
+			let battles = PendingBattles::<T>::take(n);
+			let battle_count = battles.len() as u64;
+			for challenge_id in battles {
+				Self::fight(challenge_id);
+			}
+
+			T::DbWeight::get().reads_writes(1 + count, 1 + count).saturating_add(
+				T::DbWeight::get().reads_writes(1 + 7 * battle_count, 1 + 6 * battle_count),
+			)
+		}
+
+		fn offchain_worker(n: BlockNumberFor<T>) {
+			if let Err(e) = Self::commit_rarity(n) {
+				frame_support::log::warn!(
+					target: "runtime::kitties",
+					"Failed to commit rarity scores at #{:?}: {}",
+					n,
+					e
+				);
+			}
+		}
+
 		fn on_runtime_upgrade() -> Weight {
 			migrations::v2::migrate::<T>()
+				.saturating_add(migrations::v3::migrate::<T>())
+				.saturating_add(migrations::v4::migrate::<T>())
+				.saturating_add(migrations::v5::migrate::<T>())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state()
 		}
 	}
 
@@ -112,7 +780,11 @@ pub mod pallet {
 			// Check that the extrinsic was signed and get the signer.
 			let who = ensure_signed(origin)?;
 
-			let price = T::KittyPrice::get();
+			Self::ensure_mint_window_open()?;
+			Self::note_mint(&who)?;
+			Self::ensure_next_id_available()?;
+
+			let price = Self::mint_price();
 			T::Currency::transfer(
 				&who,
 				&Self::get_account_id(),
@@ -129,13 +801,21 @@ pub mod pallet {
 			// update storage
 			Kitties::<T>::insert(kitty_id, &kitty);
 			KittyOwner::<T>::insert(kitty_id, &who);
+			Self::add_to_collection(DEFAULT_COLLECTION, kitty_id);
+			KittyProgress::<T>::insert(kitty_id, Progress::default());
 
 			// Emit an event.
-			Self::deposit_event(Event::KittyCreated { who, kitty_id, kitty });
+			Self::deposit_event(Event::KittyCreated {
+				who,
+				collection_id: DEFAULT_COLLECTION,
+				kitty_id,
+				kitty,
+			});
 			// Return a successful DispatchResultWithPostInfo
 			Ok(())
 		}
 
+		/// Breed two kitties the signer currently uses, either owned or borrowed.
 		#[pallet::call_index(1)]
 		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
 		pub fn breed(
@@ -143,11 +823,17 @@ pub mod pallet {
 			kitty_id_1: KittyId,
 			kitty_id_2: KittyId,
 			name: [u8; 8],
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			// Check that the extrinsic was signed and get the signer.
 			let who = ensure_signed(origin)?;
 
-			let price = T::KittyPrice::get();
+			// Reject invalid requests before any funds move, without charging a fee for them.
+			let (kitty_1, kitty_2, collection_id) =
+				Self::validate_breed(&who, kitty_id_1, kitty_id_2).map_err(|error| {
+					DispatchErrorWithPostInfo { post_info: Pays::No.into(), error }
+				})?;
+
+			let price = Self::breed_price();
 			T::Currency::transfer(
 				&who,
 				&Self::get_account_id(),
@@ -155,43 +841,46 @@ pub mod pallet {
 				ExistenceRequirement::KeepAlive,
 			)?;
 
-			// acquire parents' data
-			let kitty_1 = Self::kitties(kitty_id_1).ok_or(Error::<T>::InvalidKittyId)?;
-			let kitty_2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyId)?;
-			// Check that parents must be two different kitty
-			ensure!(kitty_id_1 != kitty_id_2, Error::<T>::SameKittyId);
-
-			// Check both kitty_id_! and kitty_id_2 are valid
-			// already checked when acquiring parents' data
-			//ensure!(Kitties::<T>::contains_key(kitty_id_1), Error::<T>::InvalidKittyId);
-			//ensure!(Kitties::<T>::contains_key(kitty_id_2), Error::<T>::InvalidKittyId);
-
 			// get new kitty's id
 			let kitty_id = Self::get_next_id()?;
 
 			// generate child kitty's data and create child kitty
-			let selector = Self::random_value(&who);
-			let mut dna = [0u8; 16];
-			for i in 0..selector.len() {
-				// 0 choose kitty2 and 1 choose kitty1
-				dna[i] = (kitty_1.dna[i] & selector[i]) | (kitty_2.dna[i] & !selector[i]);
-			}
+			let dna = inherit_dna(
+				(kitty_1.dna, Self::level(kitty_id_1)),
+				(kitty_2.dna, Self::level(kitty_id_2)),
+				Self::random_value(&who),
+			);
 			let kitty = Kitty { dna, name };
 
 			// update storage
 			Kitties::<T>::insert(kitty_id, &kitty);
 			KittyOwner::<T>::insert(kitty_id, &who);
 			KittyParents::<T>::insert(kitty_id, (kitty_id_1, kitty_id_2));
+			KittyChildren::<T>::insert(kitty_id_1, kitty_id, ());
+			KittyChildren::<T>::insert(kitty_id_2, kitty_id, ());
+			Self::add_to_collection(collection_id, kitty_id);
+			KittyProgress::<T>::insert(kitty_id, Progress::default());
+			Self::gain_experience(kitty_id_1, T::BreedExperience::get());
+			Self::gain_experience(kitty_id_2, T::BreedExperience::get());
 
 			// Emit an event.
-			Self::deposit_event(Event::KittyBred { who, kitty_id, kitty });
+			Self::deposit_event(Event::KittyBred {
+				who,
+				kitty_id,
+				kitty,
+				parents: (kitty_id_1, kitty_id_2),
+			});
 
 			// Return a successful DispatchResultWithPostInfo
-			Ok(())
+			Ok(().into())
 		}
 
 		#[pallet::call_index(2)]
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		#[pallet::weight(
+			10_000 +
+				T::DbWeight::get().writes(1).ref_time() +
+				Pallet::<T>::invalidate_swaps_weight().ref_time()
+		)]
 		pub fn transfer(
 			origin: OriginFor<T>,
 			recipient: T::AccountId,
@@ -203,9 +892,11 @@ pub mod pallet {
 			// Check that the extrinsic was signed by kitty's owner.
 			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
 			ensure!(who == owner, Error::<T>::NotOwner);
+			Self::ensure_transferable(kitty_id)?;
 
 			// update storage
 			KittyOwner::<T>::insert(kitty_id, &recipient);
+			Self::invalidate_swaps(kitty_id);
 
 			// Emit an event.
 			Self::deposit_event(Event::KittyTransferred { who, recipient, kitty_id });
@@ -225,18 +916,19 @@ pub mod pallet {
 
 			ensure!(Self::kitty_owner(kitty_id) == Some(who.clone()), Error::<T>::NotOwner);
 			ensure!(Self::kitty_on_sale(kitty_id).is_none(), Error::<T>::AlreadyOnSale);
+			Self::ensure_transferable(kitty_id)?;
 
 			// update storage
 			KittyOnSale::<T>::insert(kitty_id, ());
 
 			// Emit an event.
-			Self::deposit_event(Event::KittyOnSale { who, kitty_id });
+			Self::deposit_event(Event::KittyOnSale { who, kitty_id, price: T::KittyPrice::get() });
 			// Return a successful DispatchResultWithPostInfo
 			Ok(())
 		}
 
 		#[pallet::call_index(4)]
-		#[pallet::weight(10_000)]
+		#[pallet::weight(10_000 + Pallet::<T>::invalidate_swaps_weight().ref_time())]
 		pub fn buy(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
 			// Check that the extrinsic was signed and get the signer.
 			let who = ensure_signed(origin)?;
@@ -246,6 +938,7 @@ pub mod pallet {
 				.ok_or::<DispatchError>(Error::<T>::InvalidKittyId.into())?;
 			ensure!(owner != who, Error::<T>::AlreadyOwned);
 			ensure!(Self::kitty_on_sale(kitty_id).is_some(), Error::<T>::NotOnSale);
+			Self::ensure_transferable(kitty_id)?;
 
 			let price = T::KittyPrice::get();
 			T::Currency::transfer(&who, &owner, price, ExistenceRequirement::KeepAlive)?;
@@ -253,36 +946,1443 @@ pub mod pallet {
 			// update storage
 			KittyOwner::<T>::insert(kitty_id, &who);
 			KittyOnSale::<T>::remove(kitty_id);
+			Self::invalidate_swaps(kitty_id);
 
 			// Emit an event.
-			Self::deposit_event(Event::KittyBought { who, kitty_id });
+			Self::deposit_event(Event::KittyBought { who, seller: owner, kitty_id, price });
 			// Return a successful DispatchResultWithPostInfo
 			Ok(())
 		}
-	}
 
-	impl<T: Config> Pallet<T> {
-		fn get_next_id() -> Result<KittyId, DispatchError> {
-			NextKittyId::<T>::try_mutate(|next_id| -> Result<KittyId, DispatchError> {
-				let current_id = *next_id;
-				*next_id = next_id
-					.checked_add(1)
-					.ok_or::<DispatchError>(Error::<T>::InvalidKittyId.into())?;
-				Ok(current_id)
-			})
+		#[pallet::call_index(5)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(5).ref_time())]
+		pub fn propose_swap(
+			origin: OriginFor<T>,
+			offered: KittyId,
+			wanted: KittyId,
+			top_up: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			// Check that the signer owns the offered kitty and not the wanted one
+			let owner = Self::kitty_owner(offered).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(owner == who, Error::<T>::NotOwner);
+			ensure!(offered != wanted, Error::<T>::SameKittyId);
+			let counterparty = Self::kitty_owner(wanted).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(counterparty != who, Error::<T>::AlreadyOwned);
+			Self::ensure_transferable(offered)?;
+			Self::ensure_transferable(wanted)?;
+
+			let swap_id = Self::get_next_swap_id()?;
+			let deposit = T::SwapDeposit::get();
+			T::Currency::reserve(&who, deposit)?;
+
+			// update storage
+			KittySwaps::<T>::try_append(offered, swap_id).map_err(|_| Error::<T>::TooManySwaps)?;
+			KittySwaps::<T>::try_append(wanted, swap_id).map_err(|_| Error::<T>::TooManySwaps)?;
+			let swap = Swap { proposer: who.clone(), offered, wanted, top_up, deposit };
+			Swaps::<T>::insert(swap_id, &swap);
+
+			// Emit an event.
+			Self::deposit_event(Event::SwapProposed { who, swap_id, offered, wanted, top_up });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
 		}
 
-		fn random_value(sender: &T::AccountId) -> [u8; 16] {
-			let payload = (
-				T::Randomness::random_seed(),
-				&sender,
-				<frame_system::Pallet<T>>::extrinsic_index(),
+		#[pallet::call_index(6)]
+		#[pallet::weight(
+			10_000 +
+				T::DbWeight::get().writes(7).ref_time() +
+				2 * Pallet::<T>::invalidate_swaps_weight().ref_time()
+		)]
+		pub fn accept_swap(origin: OriginFor<T>, swap_id: SwapId) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			let swap = Self::swaps(swap_id).ok_or(Error::<T>::InvalidSwapId)?;
+			// Only the owner of the wanted kitty can accept
+			let owner = Self::kitty_owner(swap.wanted).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(owner == who, Error::<T>::NotOwner);
+			Self::ensure_transferable(swap.offered)?;
+			Self::ensure_transferable(swap.wanted)?;
+
+			if let Some(top_up) = swap.top_up {
+				T::Currency::transfer(
+					&swap.proposer,
+					&who,
+					top_up,
+					ExistenceRequirement::KeepAlive,
+				)?;
+			}
+
+			// update storage, both kitties change hands so any other offer on them is void
+			KittyOwner::<T>::insert(swap.offered, &who);
+			KittyOwner::<T>::insert(swap.wanted, &swap.proposer);
+			KittyOnSale::<T>::remove(swap.offered);
+			KittyOnSale::<T>::remove(swap.wanted);
+			Self::remove_swap(swap_id, &swap);
+			Self::invalidate_swaps(swap.offered);
+			Self::invalidate_swaps(swap.wanted);
+
+			// Emit an event.
+			Self::deposit_event(Event::SwapAccepted { who, proposer: swap.proposer, swap_id });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(7)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(4).ref_time())]
+		pub fn cancel_swap(origin: OriginFor<T>, swap_id: SwapId) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			let swap = Self::swaps(swap_id).ok_or(Error::<T>::InvalidSwapId)?;
+			ensure!(swap.proposer == who, Error::<T>::NotSwapProposer);
+
+			// update storage
+			Self::remove_swap(swap_id, &swap);
+
+			// Emit an event.
+			Self::deposit_event(Event::SwapCancelled { who, swap_id });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(8)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2).ref_time())]
+		pub fn lend(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			borrower: T::AccountId,
+			until_block: T::BlockNumber,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(owner == who, Error::<T>::NotOwner);
+			ensure!(borrower != who, Error::<T>::AlreadyOwned);
+			// a listed kitty could neither be bought nor delisted while it is lent out
+			ensure!(Self::kitty_on_sale(kitty_id).is_none(), Error::<T>::AlreadyOnSale);
+			Self::ensure_transferable(kitty_id)?;
+			ensure!(
+				until_block > <frame_system::Pallet<T>>::block_number(),
+				Error::<T>::InvalidLoanPeriod
 			);
-			payload.using_encoded(blake2_128)
+
+			// update storage
+			LoanExpiries::<T>::try_append(until_block, kitty_id)
+				.map_err(|_| Error::<T>::TooManyLoans)?;
+			KittyLoans::<T>::insert(
+				kitty_id,
+				Loan { borrower: borrower.clone(), until: until_block },
+			);
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyLent { who, borrower, kitty_id, until: until_block });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
 		}
 
-		fn get_account_id() -> T::AccountId {
-			T::PalletId::get().into_account_truncating()
+		#[pallet::call_index(9)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		pub fn lock(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(owner == who, Error::<T>::NotOwner);
+			Self::ensure_unlocked(kitty_id)?;
+
+			// update storage
+			KittyLocks::<T>::insert(kitty_id, LockReason::Owner);
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyLocked { kitty_id, reason: LockReason::Owner });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Lift the lock on a kitty. Owners can only lift their own locks, while
+		/// `FreezeOrigin` can lift any lock.
+		#[pallet::call_index(10)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		pub fn unlock(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			let signer = match T::FreezeOrigin::try_origin(origin) {
+				Ok(_) => None,
+				Err(origin) => Some(ensure_signed(origin)?),
+			};
+
+			let reason = Self::kitty_locks(kitty_id).ok_or(Error::<T>::NotLocked)?;
+			if let Some(who) = signer {
+				ensure!(Self::kitty_owner(kitty_id) == Some(who), Error::<T>::NotOwner);
+				ensure!(reason == LockReason::Owner, Error::<T>::KittyLocked);
+			}
+
+			// update storage
+			KittyLocks::<T>::remove(kitty_id);
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyUnlocked { kitty_id });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(11)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		pub fn force_freeze(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			T::FreezeOrigin::ensure_origin(origin)?;
+
+			ensure!(Kitties::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyId);
+
+			// update storage, a freeze replaces any lock put by the owner
+			KittyLocks::<T>::insert(kitty_id, LockReason::Frozen);
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyLocked { kitty_id, reason: LockReason::Frozen });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Move a kitty to `new_owner` regardless of locks, loans, listings and open swaps.
+		/// A freeze stays in place, anything else tied to the previous owner is cleared.
+		#[pallet::call_index(12)]
+		#[pallet::weight(
+			10_000 +
+				T::DbWeight::get().writes(5).ref_time() +
+				Pallet::<T>::invalidate_swaps_weight().ref_time()
+		)]
+		pub fn force_transfer(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			new_owner: T::AccountId,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+
+			// update storage
+			KittyOwner::<T>::insert(kitty_id, &new_owner);
+			KittyOnSale::<T>::remove(kitty_id);
+			KittyLocks::<T>::mutate_exists(kitty_id, |lock| {
+				if *lock == Some(LockReason::Owner) {
+					*lock = None;
+				}
+			});
+			Self::end_loan(kitty_id);
+			Self::invalidate_swaps(kitty_id);
+			Self::remove_fractions(kitty_id);
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyForceTransferred {
+				kitty_id,
+				from: owner,
+				to: new_owner,
+			});
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Destroy a kitty and everything attached to it. Its children keep their parents entry.
+		#[pallet::call_index(13)]
+		#[pallet::weight(
+			10_000 +
+				T::DbWeight::get().writes(7).ref_time() +
+				Pallet::<T>::clear_attributes_weight().ref_time() +
+				Pallet::<T>::invalidate_swaps_weight().ref_time()
+		)]
+		pub fn force_burn(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			let owner = KittyOwner::<T>::take(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+
+			// update storage
+			Kitties::<T>::remove(kitty_id);
+			if let Some((parent_1, parent_2)) = KittyParents::<T>::take(kitty_id) {
+				KittyChildren::<T>::remove(parent_1, kitty_id);
+				KittyChildren::<T>::remove(parent_2, kitty_id);
+			}
+			KittyOnSale::<T>::remove(kitty_id);
+			KittyLocks::<T>::remove(kitty_id);
+			CollectionKitties::<T>::remove(KittyCollection::<T>::take(kitty_id), kitty_id);
+			KittyProgress::<T>::remove(kitty_id);
+			BattleRecords::<T>::remove(kitty_id);
+			if let Some((_, deposit)) = KittyMetadata::<T>::take(kitty_id) {
+				T::Currency::unreserve(&deposit.depositor, deposit.amount);
+			}
+			for (_, (_, deposit)) in KittyAttributes::<T>::drain_prefix(kitty_id) {
+				T::Currency::unreserve(&deposit.depositor, deposit.amount);
+			}
+			KittyAttributeCount::<T>::remove(kitty_id);
+			Self::end_loan(kitty_id);
+			Self::invalidate_swaps(kitty_id);
+			Self::remove_fractions(kitty_id);
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyBurned { kitty_id, owner });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(14)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		pub fn force_set_dna(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			dna: [u8; 16],
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			// update storage
+			Kitties::<T>::try_mutate(kitty_id, |kitty| -> DispatchResult {
+				let kitty = kitty.as_mut().ok_or(Error::<T>::InvalidKittyId)?;
+				kitty.dna = dna;
+				Ok(())
+			})?;
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyDnaSet { kitty_id, dna });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(15)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		pub fn force_delist(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			ensure!(Self::kitty_on_sale(kitty_id).is_some(), Error::<T>::NotOnSale);
+
+			// update storage
+			KittyOnSale::<T>::remove(kitty_id);
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyDelisted { kitty_id });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(16)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		pub fn set_mint_window(
+			origin: OriginFor<T>,
+			open: Option<T::BlockNumber>,
+			close: Option<T::BlockNumber>,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			// update storage
+			MintWindow::<T>::put((open, close));
+
+			// Emit an event.
+			Self::deposit_event(Event::MintWindowSet { open, close });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(17)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		pub fn set_price_curve(origin: OriginFor<T>, params: CurveParams) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			// update storage
+			PriceCurveParams::<T>::put(params);
+
+			// Emit an event.
+			Self::deposit_event(Event::PriceCurveSet { params });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(18)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2).ref_time())]
+		pub fn create_collection(
+			origin: OriginFor<T>,
+			metadata: BoundedVec<u8, T::MaxCollectionMetadataLen>,
+			mint_price: BalanceOf<T>,
+			max_supply: u32,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			let collection_id =
+				NextCollectionId::<T>::try_mutate(|next_id| -> Result<_, DispatchError> {
+					let current_id = *next_id;
+					*next_id = next_id
+						.checked_add(1)
+						.ok_or::<DispatchError>(Error::<T>::InvalidCollectionId.into())?;
+					Ok(current_id)
+				})?;
+
+			// update storage
+			Collections::<T>::insert(
+				collection_id,
+				Collection { owner: who.clone(), metadata, mint_price, max_supply, supply: 0 },
+			);
+
+			// Emit an event.
+			Self::deposit_event(Event::CollectionCreated {
+				who,
+				collection_id,
+				mint_price,
+				max_supply,
+			});
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Mint a kitty into a collection created with `create_collection`, paying its owner the
+		/// collection's mint price along `PriceCurve`. Counts against the mint window and the
+		/// per-account allowance like `create`.
+		#[pallet::call_index(19)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(6).ref_time())]
+		pub fn mint(
+			origin: OriginFor<T>,
+			collection_id: CollectionId,
+			name: [u8; 8],
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			let collection =
+				Self::collections(collection_id).ok_or(Error::<T>::InvalidCollectionId)?;
+			ensure!(collection.supply < collection.max_supply, Error::<T>::SupplyCapReached);
+			Self::ensure_mint_window_open()?;
+			Self::note_mint(&who)?;
+			Self::ensure_next_id_available()?;
+
+			let price = Self::collection_mint_price(&collection);
+			T::Currency::transfer(&who, &collection.owner, price, ExistenceRequirement::KeepAlive)?;
+
+			// get new kitty's id
+			let kitty_id = Self::get_next_id()?;
+			// create a new kitty
+			let dna = Self::random_value(&who);
+			let kitty = Kitty { dna, name };
+
+			// update storage
+			Kitties::<T>::insert(kitty_id, &kitty);
+			KittyOwner::<T>::insert(kitty_id, &who);
+			Self::add_to_collection(collection_id, kitty_id);
+			KittyProgress::<T>::insert(kitty_id, Progress::default());
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyCreated { who, collection_id, kitty_id, kitty });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Set the off-chain metadata of a kitty, reserving `DepositPerByte` for each byte.
+		#[pallet::call_index(20)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2).ref_time())]
+		pub fn set_metadata(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			data: MetadataOf<T>,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			// Check that the extrinsic was signed by kitty's owner.
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(who == owner, Error::<T>::NotOwner);
+
+			let old_deposit = Self::kitty_metadata(kitty_id).map(|(_, deposit)| deposit);
+			let deposit = Self::replace_deposit(&who, data.len(), old_deposit)?;
+
+			// update storage
+			KittyMetadata::<T>::insert(kitty_id, (&data, deposit));
+
+			// Emit an event.
+			Self::deposit_event(Event::MetadataSet { kitty_id, data });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(21)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2).ref_time())]
+		pub fn clear_metadata(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			// Check that the extrinsic was signed by kitty's owner.
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(who == owner, Error::<T>::NotOwner);
+
+			// update storage
+			let (_, deposit) = KittyMetadata::<T>::take(kitty_id).ok_or(Error::<T>::NoMetadata)?;
+			T::Currency::unreserve(&deposit.depositor, deposit.amount);
+
+			// Emit an event.
+			Self::deposit_event(Event::MetadataCleared { kitty_id });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Set an attribute of a kitty, reserving `DepositPerByte` for each byte of its key and
+		/// value.
+		#[pallet::call_index(22)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2).ref_time())]
+		pub fn set_attribute(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			key: AttributeKeyOf<T>,
+			value: AttributeValueOf<T>,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			// Check that the extrinsic was signed by kitty's owner.
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(who == owner, Error::<T>::NotOwner);
+
+			let old_deposit = Self::kitty_attributes(kitty_id, &key).map(|(_, deposit)| deposit);
+			if old_deposit.is_none() {
+				let count = Self::kitty_attribute_count(kitty_id);
+				ensure!(count < T::MaxAttributesPerKitty::get(), Error::<T>::TooManyAttributes);
+				KittyAttributeCount::<T>::insert(kitty_id, count + 1);
+			}
+			let deposit = Self::replace_deposit(&who, key.len() + value.len(), old_deposit)?;
+
+			// update storage
+			KittyAttributes::<T>::insert(kitty_id, &key, (&value, deposit));
+
+			// Emit an event.
+			Self::deposit_event(Event::AttributeSet { kitty_id, key, value });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(23)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2).ref_time())]
+		pub fn clear_attribute(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			key: AttributeKeyOf<T>,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			// Check that the extrinsic was signed by kitty's owner.
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(who == owner, Error::<T>::NotOwner);
+
+			// update storage
+			let (_, deposit) =
+				KittyAttributes::<T>::take(kitty_id, &key).ok_or(Error::<T>::NoAttribute)?;
+			KittyAttributeCount::<T>::mutate_exists(kitty_id, |count| {
+				*count = count.map(|count| count.saturating_sub(1)).filter(|count| *count > 0);
+			});
+			T::Currency::unreserve(&deposit.depositor, deposit.amount);
+
+			// Emit an event.
+			Self::deposit_event(Event::AttributeCleared { kitty_id, key });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Feed a kitty for `FeedFee`, giving it `FeedExperience`. Only the account using the
+		/// kitty, its borrower while it is lent out, can feed it.
+		#[pallet::call_index(24)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2).ref_time())]
+		pub fn feed(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			let user = Self::kitty_user(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(who == user, Error::<T>::NotOwner);
+
+			T::Currency::transfer(
+				&who,
+				&Self::get_account_id(),
+				T::FeedFee::get(),
+				ExistenceRequirement::KeepAlive,
+			)?;
+
+			// update storage
+			let experience = Self::gain_experience(kitty_id, T::FeedExperience::get());
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyFed { who, kitty_id, experience });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Challenge kitty `opponent` to a battle with `kitty_id`, reserving `stake` until the
+		/// challenge is accepted or refunded.
+		#[pallet::call_index(25)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(3).ref_time())]
+		pub fn challenge(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			opponent: KittyId,
+			stake: BalanceOf<T>,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			// Check that the signer uses the challenging kitty and not the opponent
+			let user = Self::kitty_user(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(who == user, Error::<T>::NotOwner);
+			ensure!(kitty_id != opponent, Error::<T>::SameKittyId);
+			let opponent_user = Self::kitty_user(opponent).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(opponent_user != who, Error::<T>::AlreadyOwned);
+
+			let challenge_id = Self::get_next_challenge_id()?;
+			T::Currency::reserve(&who, stake)?;
+
+			// update storage
+			let expires =
+				<frame_system::Pallet<T>>::block_number().saturating_add(T::ChallengePeriod::get());
+			Challenges::<T>::insert(
+				challenge_id,
+				Challenge { challenger: who.clone(), kitty: kitty_id, opponent, stake, expires },
+			);
+
+			// Emit an event.
+			Self::deposit_event(Event::ChallengeIssued {
+				who,
+				challenge_id,
+				kitty_id,
+				opponent,
+				stake,
+				expires,
+			});
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Accept a challenge against a kitty the signer uses, matching its stake. The battle is
+		/// fought at the start of the next block, seeded with the hash of the block the challenge
+		/// is accepted in, and the winner takes both stakes.
+		#[pallet::call_index(26)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(3).ref_time())]
+		pub fn accept_challenge(origin: OriginFor<T>, challenge_id: ChallengeId) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			let challenge = Self::challenges(challenge_id).ok_or(Error::<T>::InvalidChallengeId)?;
+			ensure!(
+				!ChallengeAcceptors::<T>::contains_key(challenge_id),
+				Error::<T>::ChallengeAccepted
+			);
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(now < challenge.expires, Error::<T>::ChallengeExpired);
+			let user = Self::kitty_user(challenge.opponent).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(who == user, Error::<T>::NotOwner);
+			// The challenger must still be using its kitty
+			let challenger = Self::kitty_user(challenge.kitty).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(challenger == challenge.challenger, Error::<T>::NotOwner);
+
+			T::Currency::reserve(&who, challenge.stake)?;
+
+			// update storage
+			let battle_at = now.saturating_add(One::one());
+			PendingBattles::<T>::try_append(battle_at, challenge_id)
+				.map_err(|_| Error::<T>::TooManyBattles)?;
+			ChallengeAcceptors::<T>::insert(challenge_id, &who);
+
+			// Emit an event.
+			Self::deposit_event(Event::ChallengeAccepted { who, challenge_id, battle_at });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Return the stake of a challenge to its challenger, who can withdraw it at any time.
+		/// Anyone can clear a challenge once it has expired.
+		#[pallet::call_index(27)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2).ref_time())]
+		pub fn refund_challenge(origin: OriginFor<T>, challenge_id: ChallengeId) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			let challenge = Self::challenges(challenge_id).ok_or(Error::<T>::InvalidChallengeId)?;
+			ensure!(
+				!ChallengeAcceptors::<T>::contains_key(challenge_id),
+				Error::<T>::ChallengeAccepted
+			);
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(
+				who == challenge.challenger || now >= challenge.expires,
+				Error::<T>::ChallengeNotExpired
+			);
+
+			// update storage
+			T::Currency::unreserve(&challenge.challenger, challenge.stake);
+			Challenges::<T>::remove(challenge_id);
+
+			// Emit an event.
+			Self::deposit_event(Event::ChallengeRefunded { challenge_id });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Lock a kitty in the pallet account and issue `shares` shares of it to its owner. The
+		/// kitty can't be transferred, sold or bred until all shares are redeemed.
+		#[pallet::call_index(28)]
+		#[pallet::weight(
+			10_000 +
+				T::DbWeight::get().writes(3).ref_time() +
+				Pallet::<T>::invalidate_swaps_weight().ref_time()
+		)]
+		pub fn fractionalize(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			shares: Shares,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			// Check that the extrinsic was signed by kitty's owner.
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(who == owner, Error::<T>::NotOwner);
+			ensure!(shares > 0, Error::<T>::InvalidShares);
+			ensure!(Self::kitty_on_sale(kitty_id).is_none(), Error::<T>::AlreadyOnSale);
+			Self::ensure_transferable(kitty_id)?;
+
+			// update storage
+			KittyOwner::<T>::insert(kitty_id, Self::get_account_id());
+			KittyFractions::<T>::insert(kitty_id, shares);
+			KittyShares::<T>::insert(kitty_id, &who, shares);
+			Self::invalidate_swaps(kitty_id);
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyFractionalized { who, kitty_id, shares });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		#[pallet::call_index(29)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2).ref_time())]
+		pub fn transfer_shares(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			recipient: T::AccountId,
+			amount: Shares,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			ensure!(KittyFractions::<T>::contains_key(kitty_id), Error::<T>::NotFractionalized);
+			ensure!(amount > 0, Error::<T>::InvalidShares);
+
+			// update storage
+			KittyShares::<T>::try_mutate_exists(kitty_id, &who, |shares| -> DispatchResult {
+				let remaining = shares
+					.unwrap_or_default()
+					.checked_sub(amount)
+					.ok_or(Error::<T>::InsufficientShares)?;
+				*shares = Some(remaining).filter(|remaining| *remaining > 0);
+				Ok(())
+			})?;
+			KittyShares::<T>::mutate(kitty_id, &recipient, |shares| {
+				*shares = shares.saturating_add(amount)
+			});
+
+			// Emit an event.
+			Self::deposit_event(Event::SharesTransferred {
+				kitty_id,
+				from: who,
+				to: recipient,
+				amount,
+			});
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Take ownership of a fractionalized kitty by surrendering all of its shares.
+		#[pallet::call_index(30)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(3).ref_time())]
+		pub fn redeem(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			// Check that the extrinsic was signed and get the signer.
+			let who = ensure_signed(origin)?;
+
+			let total = Self::kitty_fractions(kitty_id).ok_or(Error::<T>::NotFractionalized)?;
+			ensure!(Self::kitty_shares(kitty_id, &who) == total, Error::<T>::InsufficientShares);
+
+			// update storage
+			Self::remove_fractions(kitty_id);
+			KittyOwner::<T>::insert(kitty_id, &who);
+
+			// Emit an event.
+			Self::deposit_event(Event::KittyRedeemed { who, kitty_id });
+			// Return a successful DispatchResultWithPostInfo
+			Ok(())
+		}
+
+		/// Commit the Merkle root of the kitties' rarity scores. Submitted unsigned by the
+		/// offchain worker, with the payload signed by a rarity committer.
+		#[pallet::call_index(31)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2).ref_time())]
+		pub fn submit_rarity_root(
+			origin: OriginFor<T>,
+			payload: RarityPayload<T::Public, T::BlockNumber>,
+			_signature: T::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			ensure!(
+				RarityCommitters::<T>::contains_key(payload.public.clone().into_account()),
+				Error::<T>::NotRarityCommitter
+			);
+			ensure!(payload.block_number >= Self::next_rarity_at(), Error::<T>::StaleRarityRoot);
+
+			// update storage
+			let RarityPayload { block_number, root, kitty_count, .. } = payload;
+			RarityRoot::<T>::put(RarityCommitment { block_number, root, kitty_count });
+			NextRarityAt::<T>::put(block_number.saturating_add(T::RarityInterval::get()));
+
+			// Emit an event.
+			Self::deposit_event(Event::RarityRootCommitted { block_number, root, kitty_count });
+			Ok(())
+		}
+
+		/// Allow or disallow `who` to sign rarity roots.
+		#[pallet::call_index(32)]
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		pub fn set_rarity_committer(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			allowed: bool,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			if allowed {
+				RarityCommitters::<T>::insert(&who, ());
+			} else {
+				RarityCommitters::<T>::remove(&who);
+			}
+
+			Self::deposit_event(Event::RarityCommitterSet { who, allowed });
+			Ok(())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		/// Accept rarity roots signed by a committer for a block that is neither in the future
+		/// nor already covered by the latest root. Roots for the same interval provide the same
+		/// tag, so only one of them is kept in the pool.
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let (payload, signature) = match call {
+				Call::submit_rarity_root { payload, signature } => (payload, signature),
+				_ => return InvalidTransaction::Call.into(),
+			};
+			if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
+				return InvalidTransaction::BadProof.into()
+			}
+			if !RarityCommitters::<T>::contains_key(payload.public.clone().into_account()) {
+				return InvalidTransaction::BadSigner.into()
+			}
+			if payload.block_number > <frame_system::Pallet<T>>::block_number() {
+				return InvalidTransaction::Future.into()
+			}
+			let next_rarity_at = Self::next_rarity_at();
+			if payload.block_number < next_rarity_at {
+				return InvalidTransaction::Stale.into()
+			}
+
+			ValidTransaction::with_tag_prefix("KittiesRarity")
+				.priority(T::RarityPriority::get())
+				.and_provides(next_rarity_at)
+				.longevity(T::RarityInterval::get().unique_saturated_into())
+				.propagate(true)
+				.build()
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn get_next_id() -> Result<KittyId, DispatchError> {
+			NextKittyId::<T>::try_mutate(|next_id| -> Result<KittyId, DispatchError> {
+				let current_id = *next_id;
+				*next_id = next_id
+					.checked_add(1)
+					.ok_or::<DispatchError>(Error::<T>::InvalidKittyId.into())?;
+				Ok(current_id)
+			})
+		}
+
+		/// The number of kitties that can still be created before `MaxSupply` is reached.
+		pub fn remaining_supply() -> u32 {
+			T::MaxSupply::get().saturating_sub(Self::next_kitty_id())
+		}
+
+		/// The price of the next minted kitty, following `PriceCurve` over the supply.
+		pub fn mint_price() -> BalanceOf<T> {
+			T::PriceCurve::price(
+				T::MintPrice::get(),
+				Self::next_kitty_id(),
+				&Self::price_curve_params(),
+			)
+		}
+
+		/// The price of the next kitty minted into `collection`, following `PriceCurve` over the
+		/// collection's own supply from its mint price.
+		pub fn collection_mint_price(collection: &CollectionOf<T>) -> BalanceOf<T> {
+			T::PriceCurve::price(
+				collection.mint_price,
+				collection.supply,
+				&Self::price_curve_params(),
+			)
+		}
+
+		/// The price of the next bred kitty, following `PriceCurve` over the supply.
+		pub fn breed_price() -> BalanceOf<T> {
+			T::PriceCurve::price(
+				T::BreedPrice::get(),
+				Self::next_kitty_id(),
+				&Self::price_curve_params(),
+			)
+		}
+
+		/// Everything known about a kitty, or `None` if it doesn't exist.
+		pub fn kitty_info(kitty_id: KittyId) -> Option<KittyInfo<T::AccountId>> {
+			let kitty = Self::kitties(kitty_id)?;
+			let owner = Self::kitty_owner(kitty_id)?;
+			Some(KittyInfo {
+				owner,
+				kitty,
+				parents: Self::kitty_parents(kitty_id),
+				collection_id: Self::kitty_collection(kitty_id),
+				progress: Self::kitty_progress(kitty_id).unwrap_or_default(),
+				record: Self::battle_records(kitty_id),
+				on_sale: Self::kitty_on_sale(kitty_id).is_some(),
+				metadata: Self::kitty_metadata(kitty_id)
+					.map(|(data, _)| data.into_inner())
+					.unwrap_or_default(),
+				attributes: KittyAttributes::<T>::iter_prefix(kitty_id)
+					.map(|(key, (value, _))| (key.into_inner(), value.into_inner()))
+					.collect(),
+			})
+		}
+
+		/// The ancestors and descendants of a kitty up to `depth` generations, or `None` if it
+		/// doesn't exist.
+		pub fn family_tree(kitty_id: KittyId, depth: u32) -> Option<FamilyTree> {
+			if !Kitties::<T>::contains_key(kitty_id) {
+				return None
+			}
+			Some(walk_family_tree(
+				kitty_id,
+				depth,
+				|kitty_id| Self::kitty_parents(kitty_id),
+				|kitty_id| KittyChildren::<T>::iter_key_prefix(kitty_id),
+			))
+		}
+
+		/// The nearest common ancestor of two kitties, or `None` if either doesn't exist or they
+		/// are unrelated within `MAX_FAMILY_DEPTH` generations.
+		pub fn common_ancestor(first: KittyId, second: KittyId) -> Option<CommonAncestor> {
+			if !Kitties::<T>::contains_key(first) || !Kitties::<T>::contains_key(second) {
+				return None
+			}
+			find_common_ancestor(first, second, |kitty_id| Self::kitty_parents(kitty_id))
+		}
+
+		/// The account allowed to use `kitty_id`: its borrower while on loan, else its owner.
+		pub fn kitty_user(kitty_id: KittyId) -> Option<T::AccountId> {
+			Self::kitty_loans(kitty_id)
+				.map(|loan| loan.borrower)
+				.or_else(|| Self::kitty_owner(kitty_id))
+		}
+
+		/// Kitties can't change hands while they are lent out, locked or fractionalized.
+		fn ensure_transferable(kitty_id: KittyId) -> DispatchResult {
+			ensure!(!KittyLoans::<T>::contains_key(kitty_id), Error::<T>::KittyOnLoan);
+			Self::ensure_not_fractionalized(kitty_id)?;
+			Self::ensure_unlocked(kitty_id)
+		}
+
+		fn ensure_not_fractionalized(kitty_id: KittyId) -> DispatchResult {
+			ensure!(!KittyFractions::<T>::contains_key(kitty_id), Error::<T>::KittyFractionalized);
+			Ok(())
+		}
+
+		fn remove_fractions(kitty_id: KittyId) {
+			if KittyFractions::<T>::take(kitty_id).is_some() {
+				let _ = KittyShares::<T>::clear_prefix(kitty_id, u32::MAX, None);
+			}
+		}
+
+		fn ensure_unlocked(kitty_id: KittyId) -> DispatchResult {
+			ensure!(!KittyLocks::<T>::contains_key(kitty_id), Error::<T>::KittyLocked);
+			Ok(())
+		}
+
+		/// Check that `who` can breed two kitties and that the child can be given an id, returning
+		/// the parents and the collection the child will belong to.
+		fn validate_breed(
+			who: &T::AccountId,
+			kitty_id_1: KittyId,
+			kitty_id_2: KittyId,
+		) -> Result<(Kitty, Kitty, CollectionId), DispatchError> {
+			// acquire parents' data
+			let kitty_1 = Self::kitties(kitty_id_1).ok_or(Error::<T>::InvalidKittyId)?;
+			let kitty_2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyId)?;
+			// Check that parents must be two different kitty
+			ensure!(kitty_id_1 != kitty_id_2, Error::<T>::SameKittyId);
+			Self::ensure_unlocked(kitty_id_1)?;
+			Self::ensure_unlocked(kitty_id_2)?;
+			Self::ensure_not_fractionalized(kitty_id_1)?;
+			Self::ensure_not_fractionalized(kitty_id_2)?;
+			// Only the current user of both parents may breed them, the borrower during a loan
+			ensure!(
+				Self::kitty_user(kitty_id_1).as_ref() == Some(who) &&
+					Self::kitty_user(kitty_id_2).as_ref() == Some(who),
+				Error::<T>::NotOwner
+			);
+
+			// Check that parents belong to the same collection, which has room for the child
+			let collection_id = Self::kitty_collection(kitty_id_1);
+			ensure!(
+				Self::kitty_collection(kitty_id_2) == collection_id,
+				Error::<T>::CollectionMismatch
+			);
+			if let Some(collection) = Self::collections(collection_id) {
+				ensure!(collection.supply < collection.max_supply, Error::<T>::SupplyCapReached);
+			}
+			Self::ensure_next_id_available()?;
+			Ok((kitty_1, kitty_2, collection_id))
+		}
+
+		fn level(kitty_id: KittyId) -> u32 {
+			Self::kitty_progress(kitty_id).unwrap_or_default().level
+		}
+
+		/// Add `experience` to a kitty, levelling it up when it crosses a threshold, and return
+		/// its new experience.
+		fn gain_experience(kitty_id: KittyId, experience: u32) -> u32 {
+			let mut progress = Self::kitty_progress(kitty_id).unwrap_or_default();
+			progress.experience = progress.experience.saturating_add(experience);
+			let level = level_for(progress.experience, &T::LevelThresholds::get());
+			if level > progress.level {
+				progress.level = level;
+				Self::deposit_event(Event::KittyLevelUp { kitty_id, level });
+			}
+			KittyProgress::<T>::insert(kitty_id, progress);
+			progress.experience
+		}
+
+		/// Reserve the deposit for `len` bytes from `who`, returning `old` to its depositor.
+		fn replace_deposit(
+			who: &T::AccountId,
+			len: usize,
+			old: Option<DepositOf<T>>,
+		) -> Result<DepositOf<T>, DispatchError> {
+			if let Some(old) = old {
+				T::Currency::unreserve(&old.depositor, old.amount);
+			}
+			let amount = T::DepositPerByte::get().saturating_mul((len as u32).into());
+			T::Currency::reserve(who, amount)?;
+			Ok(Deposit { depositor: who.clone(), amount })
+		}
+
+		fn add_to_collection(collection_id: CollectionId, kitty_id: KittyId) {
+			KittyCollection::<T>::insert(kitty_id, collection_id);
+			CollectionKitties::<T>::insert(collection_id, kitty_id, ());
+			Collections::<T>::mutate(collection_id, |collection| {
+				if let Some(collection) = collection {
+					collection.supply.saturating_inc();
+				}
+			});
+		}
+
+		/// Check that `get_next_id` will succeed without `MaxSupply` being exceeded.
+		fn ensure_next_id_available() -> DispatchResult {
+			let next_id = Self::next_kitty_id();
+			ensure!(next_id.checked_add(1).is_some(), Error::<T>::InvalidKittyId);
+			ensure!(next_id < T::MaxSupply::get(), Error::<T>::SupplyCapReached);
+			Ok(())
+		}
+
+		fn ensure_mint_window_open() -> DispatchResult {
+			let now = <frame_system::Pallet<T>>::block_number();
+			let (open, close) = Self::mint_window();
+			ensure!(open.map_or(true, |open| now >= open), Error::<T>::MintWindowClosed);
+			ensure!(close.map_or(true, |close| now < close), Error::<T>::MintWindowClosed);
+			Ok(())
+		}
+
+		/// Count a mint against the account's allowance for the current mint period.
+		fn note_mint(who: &T::AccountId) -> DispatchResult {
+			let now = <frame_system::Pallet<T>>::block_number();
+			let period = now / T::MintPeriod::get().max(One::one());
+			AccountMints::<T>::try_mutate(who, |(last_period, mints)| -> DispatchResult {
+				if *last_period != period {
+					*last_period = period;
+					*mints = 0;
+				}
+				ensure!(
+					*mints < T::MaxMintsPerAccountPerPeriod::get(),
+					Error::<T>::MintLimitReached
+				);
+				mints.saturating_inc();
+				Ok(())
+			})
+		}
+
+		fn end_loan(kitty_id: KittyId) {
+			if let Some(loan) = KittyLoans::<T>::take(kitty_id) {
+				LoanExpiries::<T>::mutate(loan.until, |kitty_ids| {
+					kitty_ids.retain(|id| *id != kitty_id)
+				});
+				Self::deposit_event(Event::KittyLoanEnded { borrower: loan.borrower, kitty_id });
+			}
+		}
+
+		fn get_next_swap_id() -> Result<SwapId, DispatchError> {
+			NextSwapId::<T>::try_mutate(|next_id| -> Result<SwapId, DispatchError> {
+				let current_id = *next_id;
+				*next_id = next_id
+					.checked_add(1)
+					.ok_or::<DispatchError>(Error::<T>::InvalidSwapId.into())?;
+				Ok(current_id)
+			})
+		}
+
+		fn get_next_challenge_id() -> Result<ChallengeId, DispatchError> {
+			NextChallengeId::<T>::try_mutate(|next_id| -> Result<ChallengeId, DispatchError> {
+				let current_id = *next_id;
+				*next_id = next_id
+					.checked_add(1)
+					.ok_or::<DispatchError>(Error::<T>::InvalidChallengeId.into())?;
+				Ok(current_id)
+			})
+		}
+
+		/// Close a swap, dropping it from the index of both kitties and returning its deposit.
+		fn remove_swap(swap_id: SwapId, swap: &SwapOf<T>) {
+			Swaps::<T>::remove(swap_id);
+			for kitty_id in [swap.offered, swap.wanted] {
+				KittySwaps::<T>::mutate_exists(kitty_id, |swap_ids| {
+					if let Some(ids) = swap_ids {
+						ids.retain(|id| *id != swap_id);
+						if ids.is_empty() {
+							*swap_ids = None;
+						}
+					}
+				});
+			}
+			T::Currency::unreserve(&swap.proposer, swap.deposit);
+		}
+
+		/// Drop every open swap involving `kitty_id`, called whenever the kitty changes hands.
+		fn invalidate_swaps(kitty_id: KittyId) {
+			for swap_id in KittySwaps::<T>::take(kitty_id) {
+				if let Some(swap) = Self::swaps(swap_id) {
+					Self::remove_swap(swap_id, &swap);
+					Self::deposit_event(Event::SwapInvalidated { swap_id });
+				}
+			}
+		}
+
+		/// The most clearing the attributes of a kitty can cost, `MaxAttributesPerKitty` of them
+		/// each with a deposit to return.
+		pub fn clear_attributes_weight() -> Weight {
+			let attributes = u64::from(T::MaxAttributesPerKitty::get());
+			T::DbWeight::get().reads_writes(1 + 2 * attributes, 1 + 2 * attributes)
+		}
+
+		/// The most `invalidate_swaps` can cost, closing `MaxSwapsPerKitty` swaps.
+		pub fn invalidate_swaps_weight() -> Weight {
+			let swaps = u64::from(T::MaxSwapsPerKitty::get());
+			// every swap, the other kitty's index and the proposer's reserve
+			T::DbWeight::get().reads_writes(1 + 3 * swaps, 1 + 3 * swaps)
+		}
+
+		/// Fight the battle of an accepted challenge and pay both stakes to the winner. If either
+		/// kitty was burned since the challenge was accepted, the stakes are returned instead.
+		fn fight(challenge_id: ChallengeId) {
+			let (challenge, acceptor) = match (
+				Challenges::<T>::take(challenge_id),
+				ChallengeAcceptors::<T>::take(challenge_id),
+			) {
+				(Some(challenge), Some(acceptor)) => (challenge, acceptor),
+				_ => return,
+			};
+
+			let (kitty, opponent) =
+				match (Self::kitties(challenge.kitty), Self::kitties(challenge.opponent)) {
+					(Some(kitty), Some(opponent)) => (kitty, opponent),
+					_ => {
+						T::Currency::unreserve(&challenge.challenger, challenge.stake);
+						T::Currency::unreserve(&acceptor, challenge.stake);
+						Self::deposit_event(Event::ChallengeRefunded { challenge_id });
+						return
+					},
+				};
+
+			// `Randomness` may only be updated after this hook, so its output can be known when
+			// accepting; mix in the hash of the block the challenge was accepted in
+			let parent_hash = <frame_system::Pallet<T>>::parent_hash();
+			let luck = T::Randomness::random(&(b"battle", challenge_id, parent_hash).encode()).0;
+			let (winner, loser, winner_id, loser_id) = match resolve(
+				&Stats::from_dna(&kitty.dna),
+				&Stats::from_dna(&opponent.dna),
+				luck.using_encoded(blake2_128),
+			) {
+				Winner::Challenger =>
+					(challenge.challenger, acceptor, challenge.kitty, challenge.opponent),
+				Winner::Opponent =>
+					(acceptor, challenge.challenger, challenge.opponent, challenge.kitty),
+			};
+
+			// Pay out both stakes to the winner
+			T::Currency::unreserve(&winner, challenge.stake);
+			match T::Currency::repatriate_reserved(
+				&loser,
+				&winner,
+				challenge.stake,
+				BalanceStatus::Free,
+			) {
+				Ok(missing) if missing.is_zero() => (),
+				Ok(missing) => frame_support::log::warn!(
+					target: "runtime::kitties",
+					"Battle {} paid out {:?} short of the loser's stake",
+					challenge_id,
+					missing
+				),
+				Err(e) => {
+					// don't leave the stake locked with the loser
+					T::Currency::unreserve(&loser, challenge.stake);
+					frame_support::log::warn!(
+						target: "runtime::kitties",
+						"Failed to pay out the stake of battle {}: {:?}",
+						challenge_id,
+						e
+					);
+				},
+			}
+
+			// update storage
+			BattleRecords::<T>::mutate(winner_id, |record| record.wins.saturating_inc());
+			BattleRecords::<T>::mutate(loser_id, |record| record.losses.saturating_inc());
+
+			// Emit an event.
+			Self::deposit_event(Event::BattleResolved {
+				challenge_id,
+				winner: winner_id,
+				loser: loser_id,
+				stake: challenge.stake,
+			});
+		}
+
+		fn random_value(sender: &T::AccountId) -> [u8; 16] {
+			let payload = (
+				T::Randomness::random_seed(),
+				&sender,
+				<frame_system::Pallet<T>>::extrinsic_index(),
+			);
+			payload.using_encoded(blake2_128)
+		}
+
+		fn get_account_id() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// Every `RarityInterval` blocks, score all kitties, keep the scores in offchain storage
+		/// and submit their Merkle root, unless the latest root already covers this interval.
+		fn commit_rarity(block_number: T::BlockNumber) -> Result<(), &'static str> {
+			let interval = T::RarityInterval::get();
+			if interval.is_zero() ||
+				!(block_number % interval).is_zero() ||
+				block_number < Self::next_rarity_at()
+			{
+				return Ok(())
+			}
+
+			let mut kitties: Vec<(KittyId, [u8; 16])> =
+				Kitties::<T>::iter().map(|(kitty_id, kitty)| (kitty_id, kitty.dna)).collect();
+			kitties.sort_unstable_by_key(|(kitty_id, _)| *kitty_id);
+			let scores = rarity_scores(&kitties);
+			StorageValueRef::persistent(RARITY_SCORES_KEY).set(&(block_number, &scores));
+
+			let root =
+				merkle_root(scores.iter().map(|(id, score)| rarity_leaf(*id, *score)).collect());
+			let kitty_count = scores.len() as u32;
+			let (_, result) = Signer::<T, T::AuthorityId>::any_account()
+				.send_unsigned_transaction(
+					|account| RarityPayload {
+						block_number,
+						root,
+						kitty_count,
+						public: account.public.clone(),
+					},
+					|payload, signature| Call::submit_rarity_root { payload, signature },
+				)
+				.ok_or("no rarity key in the keystore")?;
+			result.map_err(|()| "the transaction pool rejected the rarity root")
+		}
+	}
+
+	#[cfg(any(feature = "try-runtime", test))]
+	impl<T: Config> Pallet<T> {
+		/// Check that the kitty storage items are mutually consistent.
+		pub fn do_try_state() -> Result<(), &'static str> {
+			let next_id = Self::next_kitty_id();
+
+			// every kitty has an owner and an id that was handed out by `get_next_id`
+			for kitty_id in Kitties::<T>::iter_keys() {
+				ensure!(kitty_id < next_id, "kitty id is not below NextKittyId");
+				ensure!(KittyOwner::<T>::contains_key(kitty_id), "kitty has no owner");
+			}
+			// every owner entry points at a kitty
+			for kitty_id in KittyOwner::<T>::iter_keys() {
+				ensure!(Kitties::<T>::contains_key(kitty_id), "owner entry for missing kitty");
+			}
+			// parents were created before their children, though they may have been burned since
+			for (kitty_id, (parent_1, parent_2)) in KittyParents::<T>::iter() {
+				ensure!(Kitties::<T>::contains_key(kitty_id), "parents entry for missing kitty");
+				ensure!(parent_1 != parent_2, "kitty bred from a single parent");
+				for parent in [parent_1, parent_2] {
+					ensure!(parent < kitty_id, "kitty parent does not precede its child");
+					ensure!(
+						KittyChildren::<T>::contains_key(parent, kitty_id),
+						"kitty missing from its parent's children"
+					);
+				}
+			}
+			// children are indexed exactly for the parents entries
+			for (parent, kitty_id, ()) in KittyChildren::<T>::iter() {
+				let parents = Self::kitty_parents(kitty_id).ok_or("child entry without parents")?;
+				ensure!(parent == parents.0 || parent == parents.1, "child entry for non-parent");
+			}
+			// listings reference owned kitties
+			for kitty_id in KittyOnSale::<T>::iter_keys() {
+				ensure!(KittyOwner::<T>::contains_key(kitty_id), "listing for unowned kitty");
+			}
+			// open swaps are still valid and indexed under both kitties
+			for (swap_id, swap) in Swaps::<T>::iter() {
+				ensure!(swap_id < Self::next_swap_id(), "swap id is not below NextSwapId");
+				ensure!(
+					Self::kitty_owner(swap.offered) == Some(swap.proposer.clone()),
+					"swap proposer does not own the offered kitty"
+				);
+				ensure!(KittyOwner::<T>::contains_key(swap.wanted), "swap wants unowned kitty");
+				ensure!(
+					Self::kitty_swaps(swap.offered).contains(&swap_id) &&
+						Self::kitty_swaps(swap.wanted).contains(&swap_id),
+					"swap missing from the kitty index"
+				);
+			}
+			for (kitty_id, swap_ids) in KittySwaps::<T>::iter() {
+				ensure!(!swap_ids.is_empty(), "empty kitty swap index");
+				for swap_id in swap_ids {
+					let swap = Self::swaps(swap_id).ok_or("kitty index references missing swap")?;
+					ensure!(
+						swap.offered == kitty_id || swap.wanted == kitty_id,
+						"kitty index references unrelated swap"
+					);
+				}
+			}
+			// loans are for unlisted kitties and queued for expiry exactly once
+			for (kitty_id, loan) in KittyLoans::<T>::iter() {
+				ensure!(KittyOwner::<T>::contains_key(kitty_id), "loan of unowned kitty");
+				ensure!(!KittyOnSale::<T>::contains_key(kitty_id), "lent kitty is on sale");
+				let queued = LoanExpiries::<T>::get(loan.until);
+				ensure!(
+					queued.iter().filter(|id| **id == kitty_id).count() == 1,
+					"loan not queued for expiry"
+				);
+			}
+			// every kitty belongs to exactly one known collection, within its supply
+			for kitty_id in Kitties::<T>::iter_keys() {
+				let collection_id = Self::kitty_collection(kitty_id);
+				ensure!(
+					KittyCollection::<T>::contains_key(kitty_id),
+					"kitty belongs to no collection"
+				);
+				ensure!(
+					CollectionKitties::<T>::contains_key(collection_id, kitty_id),
+					"kitty missing from its collection"
+				);
+				ensure!(
+					collection_id == DEFAULT_COLLECTION ||
+						Collections::<T>::contains_key(collection_id),
+					"kitty belongs to missing collection"
+				);
+			}
+			for (collection_id, kitty_id, ()) in CollectionKitties::<T>::iter() {
+				ensure!(
+					KittyCollection::<T>::get(kitty_id) == collection_id,
+					"collection holds a kitty of another collection"
+				);
+			}
+			for (collection_id, collection) in Collections::<T>::iter() {
+				ensure!(collection_id != DEFAULT_COLLECTION, "default collection is registered");
+				ensure!(
+					collection_id < Self::next_collection_id(),
+					"collection id is not below NextCollectionId"
+				);
+				ensure!(collection.supply <= collection.max_supply, "collection supply exceeded");
+			}
+			for kitty_id in Kitties::<T>::iter_keys() {
+				ensure!(KittyProgress::<T>::contains_key(kitty_id), "kitty without progress");
+			}
+			for kitty_id in KittyProgress::<T>::iter_keys() {
+				ensure!(Kitties::<T>::contains_key(kitty_id), "progress of missing kitty");
+			}
+			// fractionalized kitties are held by the pallet, their shares add up to the total
+			for (kitty_id, total) in KittyFractions::<T>::iter() {
+				ensure!(
+					Self::kitty_owner(kitty_id) == Some(Self::get_account_id()),
+					"fractionalized kitty not held by the pallet"
+				);
+				let issued = KittyShares::<T>::iter_prefix_values(kitty_id)
+					.fold(0 as Shares, |sum, shares| sum.saturating_add(shares));
+				ensure!(issued == total, "shares don't add up to the total issued");
+			}
+			for (kitty_id, _) in KittyShares::<T>::iter_keys() {
+				ensure!(
+					KittyFractions::<T>::contains_key(kitty_id),
+					"shares of a kitty that isn't fractionalized"
+				);
+			}
+			for challenge_id in Challenges::<T>::iter_keys() {
+				ensure!(
+					challenge_id < Self::next_challenge_id(),
+					"challenge id is not below NextChallengeId"
+				);
+			}
+			for challenge_id in ChallengeAcceptors::<T>::iter_keys() {
+				ensure!(
+					Challenges::<T>::contains_key(challenge_id),
+					"acceptor of missing challenge"
+				);
+			}
+			let pending: Vec<ChallengeId> = PendingBattles::<T>::iter_values().flatten().collect();
+			ensure!(
+				pending.len() == ChallengeAcceptors::<T>::iter_keys().count() &&
+					pending.iter().all(|id| ChallengeAcceptors::<T>::contains_key(id)),
+				"accepted challenges and pending battles differ"
+			);
+			for kitty_id in KittyMetadata::<T>::iter_keys() {
+				ensure!(Kitties::<T>::contains_key(kitty_id), "metadata of missing kitty");
+			}
+			for (kitty_id, _) in KittyAttributes::<T>::iter_keys() {
+				ensure!(Kitties::<T>::contains_key(kitty_id), "attribute of missing kitty");
+			}
+			for (kitty_id, count) in KittyAttributeCount::<T>::iter() {
+				ensure!(
+					KittyAttributes::<T>::iter_key_prefix(kitty_id).count() as u32 == count,
+					"attribute count does not match the attributes set"
+				);
+			}
+			for kitty_id in KittyLocks::<T>::iter_keys() {
+				ensure!(Kitties::<T>::contains_key(kitty_id), "lock on missing kitty");
+			}
+			for (until, kitty_ids) in LoanExpiries::<T>::iter() {
+				for kitty_id in kitty_ids {
+					ensure!(
+						Self::kitty_loans(kitty_id).map(|loan| loan.until) == Some(until),
+						"expiry queued for missing loan"
+					);
+				}
+			}
+
+			Ok(())
 		}
 	}
 }
@@ -1 +1,4 @@
-pub mod v2;
\ No newline at end of file
+pub mod v2;
+pub mod v3;
+pub mod v4;
+pub mod v5;
@@ -22,11 +22,6 @@ pub struct V0Kitty(pub [u8; 16]);
 
 pub fn migrate<T: Config>() -> Weight {
 	let on_chain_version = Pallet::<T>::on_chain_storage_version();
-	let current_version = Pallet::<T>::current_storage_version();
-
-	if current_version != 2 {
-		return Weight::zero()
-	}
 
 	let module = Kitties::<T>::module_prefix();
 	let item = Kitties::<T>::storage_prefix();
@@ -51,5 +46,7 @@ pub fn migrate<T: Config>() -> Weight {
 		}
 	}
 
+	StorageVersion::new(2).put::<Pallet<T>>();
+
 	Weight::zero()
 }
@@ -0,0 +1,25 @@
+//->v3
+
+use frame_support::{pallet_prelude::*, traits::GetStorageVersion, weights::Weight};
+
+use crate::*;
+
+/// Place every existing kitty into `DEFAULT_COLLECTION`, keeping its id.
+pub fn migrate<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+
+	if on_chain_version != 2 {
+		return Weight::zero()
+	}
+
+	let mut count = 0u64;
+	for kitty_id in Kitties::<T>::iter_keys() {
+		KittyCollection::<T>::insert(kitty_id, DEFAULT_COLLECTION);
+		CollectionKitties::<T>::insert(DEFAULT_COLLECTION, kitty_id, ());
+		count += 1;
+	}
+
+	StorageVersion::new(3).put::<Pallet<T>>();
+
+	T::DbWeight::get().reads_writes(count + 1, count * 2 + 1)
+}
@@ -0,0 +1,24 @@
+//->v4
+
+use frame_support::{pallet_prelude::*, traits::GetStorageVersion, weights::Weight};
+
+use crate::*;
+
+/// Start every existing kitty at level 1 without experience.
+pub fn migrate<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+
+	if on_chain_version != 3 {
+		return Weight::zero()
+	}
+
+	let mut count = 0u64;
+	for kitty_id in Kitties::<T>::iter_keys() {
+		KittyProgress::<T>::insert(kitty_id, Progress::default());
+		count += 1;
+	}
+
+	StorageVersion::new(4).put::<Pallet<T>>();
+
+	T::DbWeight::get().reads_writes(count + 1, count + 1)
+}
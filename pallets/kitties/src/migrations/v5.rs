@@ -0,0 +1,25 @@
+//->v5
+
+use frame_support::{pallet_prelude::*, traits::GetStorageVersion, weights::Weight};
+
+use crate::*;
+
+/// Index every bred kitty under both of its parents in `KittyChildren`.
+pub fn migrate<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+
+	if on_chain_version != 4 {
+		return Weight::zero()
+	}
+
+	let mut count = 0u64;
+	for (kitty_id, (parent_1, parent_2)) in KittyParents::<T>::iter() {
+		KittyChildren::<T>::insert(parent_1, kitty_id, ());
+		KittyChildren::<T>::insert(parent_2, kitty_id, ());
+		count += 1;
+	}
+
+	StorageVersion::new(5).put::<Pallet<T>>();
+
+	T::DbWeight::get().reads_writes(count + 1, count * 2 + 1)
+}
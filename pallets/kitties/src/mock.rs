@@ -1,18 +1,22 @@
 use crate as pallet_kitties;
 use frame_support::{
 	parameter_types,
-	traits::{ConstU128, ConstU16, ConstU32, ConstU64},
+	traits::{ConstU128, ConstU16, ConstU32, ConstU64, Hooks},
 	PalletId,
 };
+use frame_system::{
+	offchain::{AppCrypto, SendTransactionTypes, SigningTypes},
+	EnsureRoot,
+};
 use pallet_balances;
 use pallet_insecure_randomness_collective_flip;
 use sp_core::H256;
 use sp_runtime::{
-	testing::Header,
+	testing::{Header, TestSignature, UintAuthorityId},
 	traits::{BlakeTwo256, IdentityLookup},
 };
 
-type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+pub type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
 
 /// Existential deposit.
@@ -64,6 +68,10 @@ impl frame_system::Config for Test {
 
 parameter_types! {
 	pub KittyPrice: Balance = EXISTENTIAL_DEPOSIT * 1000;
+	pub MintPrice: Balance = EXISTENTIAL_DEPOSIT * 100;
+	pub BreedPrice: Balance = EXISTENTIAL_DEPOSIT * 200;
+	pub FeedFee: Balance = EXISTENTIAL_DEPOSIT * 10;
+	pub LevelThresholds: Vec<u32> = vec![10, 30, 60];
 	 pub KittyPalletId:PalletId = PalletId(*b"py/kitty");
 }
 
@@ -86,7 +94,55 @@ impl pallet_kitties::Config for Test {
 	type Randomness = Randomness;
 	type Currency = Balances;
 	type KittyPrice = KittyPrice;
+	type MintPrice = MintPrice;
+	type BreedPrice = BreedPrice;
+	type PriceCurve = pallet_kitties::LinearCurve;
 	type PalletId = KittyPalletId;
+	type MaxSwapsPerKitty = ConstU32<2>;
+	type SwapDeposit = ConstU128<100>;
+	type MaxLoansPerBlock = ConstU32<2>;
+	type FreezeOrigin = EnsureRoot<u64>;
+	type ForceOrigin = EnsureRoot<u64>;
+	type MaxSupply = ConstU32<100>;
+	type MaxMintsPerAccountPerPeriod = ConstU32<5>;
+	type MintPeriod = ConstU64<10>;
+	type MaxCollectionMetadataLen = ConstU32<64>;
+	type MaxMetadataLen = ConstU32<64>;
+	type MaxAttributeKeyLen = ConstU32<16>;
+	type MaxAttributeValueLen = ConstU32<32>;
+	type MaxAttributesPerKitty = ConstU32<2>;
+	type DepositPerByte = ConstU128<10>;
+	type FeedFee = FeedFee;
+	type FeedExperience = ConstU32<10>;
+	type BreedExperience = ConstU32<5>;
+	type LevelThresholds = LevelThresholds;
+	type ChallengePeriod = ConstU64<10>;
+	type MaxBattlesPerBlock = ConstU32<2>;
+	type AuthorityId = TestAuthId;
+	type RarityInterval = ConstU64<5>;
+	type RarityPriority = ConstU64<100>;
+}
+
+/// Signs rarity roots with the keys set by `UintAuthorityId::set_all_keys`.
+pub struct TestAuthId;
+
+impl AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+}
+
+impl SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<C> SendTransactionTypes<C> for Test
+where
+	RuntimeCall: From<C>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = UncheckedExtrinsic;
 }
 
 impl pallet_insecure_randomness_collective_flip::Config for Test {}
@@ -98,3 +154,19 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	ext.execute_with(|| System::set_block_number(1));
 	ext
 }
+
+/// Advance the chain to block `n`, running the kitties `on_initialize` hook on the way.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		KittiesModule::on_initialize(System::block_number());
+	}
+}
+
+/// Run `test` in a fresh test environment and check the pallet invariants afterwards.
+pub fn build_and_execute(test: impl FnOnce()) {
+	new_test_ext().execute_with(|| {
+		test();
+		KittiesModule::do_try_state().expect("kitty storage invariants hold");
+	})
+}
@@ -0,0 +1,45 @@
+//! Experience and levels, and how levels weigh on the genes a bred kitty inherits.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// The experience a kitty has gained and the level it has reached with it.
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+pub struct Progress {
+	pub experience: u32,
+	pub level: u32,
+}
+
+impl Default for Progress {
+	/// Every kitty starts at level 1 without experience.
+	fn default() -> Self {
+		Progress { experience: 0, level: 1 }
+	}
+}
+
+/// The level reached with `experience`, where `thresholds` holds the experience needed for
+/// levels 2, 3 and so on in ascending order.
+pub fn level_for(experience: u32, thresholds: &[u32]) -> u32 {
+	let reached = thresholds.iter().take_while(|threshold| experience >= **threshold).count();
+	1 + reached as u32
+}
+
+/// Mix the DNA of two parents gene by gene, each byte being one gene. Each gene comes from
+/// one parent with a chance proportional to its level, so higher-level parents pass on more
+/// of their genes, rare ones included. `random` decides every draw.
+pub fn inherit_dna(
+	(dna_1, level_1): ([u8; 16], u32),
+	(dna_2, level_2): ([u8; 16], u32),
+	random: [u8; 16],
+) -> [u8; 16] {
+	let (weight_1, weight_2) = (u64::from(level_1.max(1)), u64::from(level_2.max(1)));
+	let mut dna = dna_2;
+	for ((gene, gene_1), draw) in dna.iter_mut().zip(dna_1).zip(random) {
+		// `draw / 256` falls below parent 1's share with probability `w1 / (w1 + w2)`.
+		if u64::from(draw) * (weight_1 + weight_2) < 256 * weight_1 {
+			*gene = gene_1;
+		}
+	}
+	dna
+}
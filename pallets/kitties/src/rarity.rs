@@ -0,0 +1,81 @@
+//! Rarity scores of kitties from how common the traits in their DNA are, and the Merkle root
+//! committing to a set of scores.
+
+use crate::KittyId;
+use codec::Encode;
+use frame_support::sp_std::vec::Vec;
+use sp_io::hashing::blake2_256;
+use sp_runtime::RuntimeDebug;
+
+/// What every trait contributes to a score, scaled by how rare its value is.
+const SCORE_SCALE: u64 = 100;
+
+/// The traits a kitty shows, read from the same DNA bytes its avatar draws them from.
+#[derive(Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub struct Traits {
+	pub pattern: u8,
+	pub eyes: u8,
+	pub ears: u8,
+	pub mouth: u8,
+}
+
+impl Traits {
+	pub fn from_dna(dna: &[u8; 16]) -> Self {
+		Traits { pattern: dna[3] % 4, eyes: dna[4] % 3, ears: dna[6] % 3, mouth: dna[7] % 3 }
+	}
+
+	fn values(&self) -> [u8; 4] {
+		[self.pattern, self.eyes, self.ears, self.mouth]
+	}
+}
+
+/// Score each kitty by the rarity of its traits among `kitties`. Every trait adds `SCORE_SCALE`
+/// times the number of kitties over the number sharing its value, so rare values add the most.
+/// Scores are returned in the order of `kitties`.
+pub fn rarity_scores(kitties: &[(KittyId, [u8; 16])]) -> Vec<(KittyId, u64)> {
+	let traits: Vec<(KittyId, [u8; 4])> =
+		kitties.iter().map(|(id, dna)| (*id, Traits::from_dna(dna).values())).collect();
+
+	// How many kitties show each value of each trait. No trait has more than four values.
+	let mut counts = [[0u64; 4]; 4];
+	for (_, values) in &traits {
+		for (trait_index, value) in values.iter().enumerate() {
+			counts[trait_index][usize::from(*value)] += 1;
+		}
+	}
+
+	let total = traits.len() as u64;
+	traits
+		.into_iter()
+		.map(|(id, values)| {
+			let score = values
+				.iter()
+				.enumerate()
+				.map(|(trait_index, value)| {
+					SCORE_SCALE * total / counts[trait_index][usize::from(*value)]
+				})
+				.sum();
+			(id, score)
+		})
+		.collect()
+}
+
+/// The Merkle leaf committing to the score of a kitty.
+pub fn rarity_leaf(kitty_id: KittyId, score: u64) -> [u8; 32] {
+	(kitty_id, score).using_encoded(blake2_256)
+}
+
+/// The root of the binary Merkle tree over `leaves`. Pairs are hashed with BLAKE2-256 and a
+/// node without a sibling moves up a level unchanged. The root of no leaves is all zeros.
+pub fn merkle_root(mut layer: Vec<[u8; 32]>) -> [u8; 32] {
+	while layer.len() > 1 {
+		layer = layer
+			.chunks(2)
+			.map(|pair| match pair {
+				[left, right] => blake2_256(&[&left[..], &right[..]].concat()),
+				_ => pair[0],
+			})
+			.collect();
+	}
+	layer.first().copied().unwrap_or_default()
+}
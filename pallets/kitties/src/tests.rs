@@ -1,9 +1,38 @@
-use crate::{mock::*, Error, Event};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	find_common_ancestor, inherit_dna, level_for, merkle_root, mock::*, rarity_leaf, rarity_scores,
+	resolve, walk_family_tree, BattleRecord, CommonAncestor, CurveParams, Error, Event,
+	ExponentialCurve, KittyId, LinearCurve, LockReason, PriceCurve, Progress, RarityCommitment,
+	RarityPayload, Relative, Stats, Winner, MAX_FAMILY_DEPTH, MAX_FAMILY_NODES, RARITY_SCORES_KEY,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::{DispatchErrorWithPostInfo, Pays},
+	traits::{GenesisBuild, GetStorageVersion, Hooks, ReservableCurrency, StorageVersion},
+	unsigned::ValidateUnsigned,
+};
+use frame_system::offchain::SignedPayload;
+use sp_core::offchain::{
+	testing::{TestOffchainExt, TestTransactionPoolExt},
+	OffchainDbExt, OffchainWorkerExt, TransactionPoolExt,
+};
+use sp_io::hashing::blake2_256;
+use sp_runtime::{
+	offchain::storage::StorageValueRef,
+	testing::UintAuthorityId,
+	traits::AccountIdConversion,
+	transaction_validity::{InvalidTransaction, TransactionSource},
+	Perbill, RuntimeAppPublic,
+};
+
+/// The error `breed` returns for requests rejected before any funds move, with the fee refunded.
+fn refunded(error: Error<Test>) -> DispatchErrorWithPostInfo {
+	DispatchErrorWithPostInfo { post_info: Pays::No.into(), error: error.into() }
+}
 
 #[test]
 fn it_works_for_create() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let kitty_id = 0;
 		let account_id = 1;
 		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
@@ -26,14 +55,20 @@ fn it_works_for_create() {
 
 		// Check event
 		System::assert_last_event(
-			Event::KittyCreated { who: account_id, kitty_id, kitty: kitty.unwrap() }.into(),
+			Event::KittyCreated {
+				who: account_id,
+				collection_id: crate::DEFAULT_COLLECTION,
+				kitty_id,
+				kitty: kitty.unwrap(),
+			}
+			.into(),
 		);
 	})
 }
 
 #[test]
 fn it_works_for_breed() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let kitty_id = 0;
 		let account_id = 1;
 
@@ -47,7 +82,7 @@ fn it_works_for_breed() {
 				kitty_id + 1,
 				*b"abcdabcd"
 			),
-			Error::<Test>::InvalidKittyId
+			refunded(Error::<Test>::InvalidKittyId)
 		);
 
 		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
@@ -59,7 +94,7 @@ fn it_works_for_breed() {
 				kitty_id,
 				*b"abcdabcd"
 			),
-			Error::<Test>::SameKittyId
+			refunded(Error::<Test>::SameKittyId)
 		);
 		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
 		// it_works_for_create() already tested this
@@ -79,18 +114,25 @@ fn it_works_for_breed() {
 		assert_eq!(kitty.is_some(), true);
 		assert_eq!(KittiesModule::kitty_owner(breed_kitty_id), Some(account_id));
 		assert_eq!(KittiesModule::kitty_parents(breed_kitty_id), Some((kitty_id, kitty_id + 1)));
+		assert_eq!(KittiesModule::kitty_children(kitty_id, breed_kitty_id), Some(()));
+		assert_eq!(KittiesModule::kitty_children(kitty_id + 1, breed_kitty_id), Some(()));
 
 		// Check event
 		System::assert_last_event(
-			Event::KittyBred { who: account_id, kitty_id: breed_kitty_id, kitty: kitty.unwrap() }
-				.into(),
+			Event::KittyBred {
+				who: account_id,
+				kitty_id: breed_kitty_id,
+				kitty: kitty.unwrap(),
+				parents: (kitty_id, kitty_id + 1),
+			}
+			.into(),
 		);
 	})
 }
 
 #[test]
 fn it_works_for_transfer() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let kitty_id = 0;
 		let account_id = 1;
 		let recipient = 2;
@@ -130,7 +172,7 @@ fn it_works_for_transfer() {
 
 #[test]
 fn it_works_for_sale() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let kitty_id = 0;
 		let account_id = 1;
 		let another_account_id = 2;
@@ -154,7 +196,9 @@ fn it_works_for_sale() {
 		//success
 		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(account_id), kitty_id));
 		assert_eq!(KittiesModule::kitty_on_sale(kitty_id).is_some(), true);
-		System::assert_last_event(crate::Event::KittyOnSale { who: account_id, kitty_id }.into());
+		System::assert_last_event(
+			Event::KittyOnSale { who: account_id, kitty_id, price: KittyPrice::get() }.into(),
+		);
 
 		assert_noop!(
 			KittiesModule::sale(RuntimeOrigin::signed(account_id), kitty_id),
@@ -165,7 +209,7 @@ fn it_works_for_sale() {
 
 #[test]
 fn it_works_for_buy() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let kitty_id = 0;
 		let seller_id = 1;
 		let buyer_id = 2;
@@ -197,7 +241,15 @@ fn it_works_for_buy() {
 		assert_ok!(KittiesModule::buy(RuntimeOrigin::signed(buyer_id), kitty_id));
 		assert_eq!(KittiesModule::kitty_owner(kitty_id), Some(buyer_id));
 		assert_eq!(KittiesModule::kitty_on_sale(kitty_id).is_some(), false);
-		System::assert_last_event(crate::Event::KittyBought{ who: buyer_id, kitty_id }.into());
+		System::assert_last_event(
+			Event::KittyBought {
+				who: buyer_id,
+				seller: seller_id,
+				kitty_id,
+				price: KittyPrice::get(),
+			}
+			.into(),
+		);
 	});
 		
 		
@@ -209,4 +261,2028 @@ fn it_works_for_buy() {
 		
 		
 	
+}
+
+#[test]
+fn it_works_for_propose_swap() {
+	build_and_execute(|| {
+		let (kitty_a, kitty_b) = (0, 1);
+		let (alice, bob) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), alice, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), bob, 1_000_000_000, 0).unwrap();
+
+		//kitty not exist
+		assert_noop!(
+			KittiesModule::propose_swap(RuntimeOrigin::signed(alice), kitty_a, kitty_b, None),
+			Error::<Test>::InvalidKittyId
+		);
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(alice), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(bob), *b"abcdabcd"));
+
+		assert_noop!(
+			KittiesModule::propose_swap(RuntimeOrigin::signed(bob), kitty_a, kitty_b, None),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			KittiesModule::propose_swap(RuntimeOrigin::signed(alice), kitty_a, kitty_a, None),
+			Error::<Test>::SameKittyId
+		);
+
+		//success
+		assert_ok!(KittiesModule::propose_swap(
+			RuntimeOrigin::signed(alice),
+			kitty_a,
+			kitty_b,
+			Some(1_000)
+		));
+		let swap_id = 0;
+		assert_eq!(KittiesModule::next_swap_id(), swap_id + 1);
+		assert_eq!(
+			KittiesModule::swaps(swap_id),
+			Some(crate::Swap {
+				proposer: alice,
+				offered: kitty_a,
+				wanted: kitty_b,
+				top_up: Some(1_000),
+				deposit: 100,
+			})
+		);
+		assert_eq!(KittiesModule::kitty_swaps(kitty_a).into_inner(), vec![swap_id]);
+		assert_eq!(KittiesModule::kitty_swaps(kitty_b).into_inner(), vec![swap_id]);
+		assert_eq!(Balances::reserved_balance(alice), 100);
+		System::assert_last_event(
+			Event::SwapProposed {
+				who: alice,
+				swap_id,
+				offered: kitty_a,
+				wanted: kitty_b,
+				top_up: Some(1_000),
+			}
+			.into(),
+		);
+	});
+}
+
+#[test]
+fn it_works_for_accept_swap() {
+	build_and_execute(|| {
+		let (kitty_a, kitty_b) = (0, 1);
+		let (alice, bob) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), alice, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), bob, 1_000_000_000, 0).unwrap();
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(alice), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(bob), *b"abcdabcd"));
+
+		//swap not exist
+		assert_noop!(
+			KittiesModule::accept_swap(RuntimeOrigin::signed(bob), 0),
+			Error::<Test>::InvalidSwapId
+		);
+
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(bob), kitty_b));
+		assert_ok!(KittiesModule::propose_swap(
+			RuntimeOrigin::signed(alice),
+			kitty_a,
+			kitty_b,
+			Some(1_000)
+		));
+
+		assert_noop!(
+			KittiesModule::accept_swap(RuntimeOrigin::signed(alice), 0),
+			Error::<Test>::NotOwner
+		);
+
+		//success
+		let alice_balance = Balances::free_balance(alice);
+		let bob_balance = Balances::free_balance(bob);
+		assert_ok!(KittiesModule::accept_swap(RuntimeOrigin::signed(bob), 0));
+		assert_eq!(KittiesModule::kitty_owner(kitty_a), Some(bob));
+		assert_eq!(KittiesModule::kitty_owner(kitty_b), Some(alice));
+		assert_eq!(KittiesModule::kitty_on_sale(kitty_b).is_some(), false);
+		assert_eq!(KittiesModule::swaps(0), None);
+		// the top up is paid and the deposit returned
+		assert_eq!(Balances::free_balance(alice), alice_balance - 1_000 + 100);
+		assert_eq!(Balances::free_balance(bob), bob_balance + 1_000);
+		assert_eq!(Balances::reserved_balance(alice), 0);
+		System::assert_last_event(
+			Event::SwapAccepted { who: bob, proposer: alice, swap_id: 0 }.into(),
+		);
+	});
+}
+
+#[test]
+fn it_works_for_cancel_swap() {
+	build_and_execute(|| {
+		let (kitty_a, kitty_b) = (0, 1);
+		let (alice, bob) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), alice, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), bob, 1_000_000_000, 0).unwrap();
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(alice), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(bob), *b"abcdabcd"));
+		assert_ok!(KittiesModule::propose_swap(
+			RuntimeOrigin::signed(alice),
+			kitty_a,
+			kitty_b,
+			None
+		));
+
+		assert_noop!(
+			KittiesModule::cancel_swap(RuntimeOrigin::signed(bob), 0),
+			Error::<Test>::NotSwapProposer
+		);
+
+		//success
+		assert_ok!(KittiesModule::cancel_swap(RuntimeOrigin::signed(alice), 0));
+		assert_eq!(KittiesModule::swaps(0), None);
+		assert_eq!(Balances::reserved_balance(alice), 0);
+		assert!(!crate::KittySwaps::<Test>::contains_key(kitty_a));
+		assert!(!crate::KittySwaps::<Test>::contains_key(kitty_b));
+		System::assert_last_event(Event::SwapCancelled { who: alice, swap_id: 0 }.into());
+
+		assert_noop!(
+			KittiesModule::accept_swap(RuntimeOrigin::signed(bob), 0),
+			Error::<Test>::InvalidSwapId
+		);
+	});
+}
+
+#[test]
+fn swaps_are_invalidated_when_kitty_changes_hands() {
+	build_and_execute(|| {
+		let (kitty_a, kitty_b) = (0, 1);
+		let (alice, bob, charlie) = (1, 2, 3);
+		Balances::set_balance(RuntimeOrigin::root(), alice, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), bob, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), charlie, 1_000_000_000, 0).unwrap();
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(alice), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(bob), *b"abcdabcd"));
+
+		// transferring the offered kitty voids the swap
+		assert_ok!(KittiesModule::propose_swap(
+			RuntimeOrigin::signed(alice),
+			kitty_a,
+			kitty_b,
+			None
+		));
+		assert_ok!(KittiesModule::transfer(RuntimeOrigin::signed(alice), charlie, kitty_a));
+		System::assert_has_event(Event::SwapInvalidated { swap_id: 0 }.into());
+		assert_eq!(KittiesModule::swaps(0), None);
+		assert_eq!(Balances::reserved_balance(alice), 0);
+		assert_noop!(
+			KittiesModule::accept_swap(RuntimeOrigin::signed(bob), 0),
+			Error::<Test>::InvalidSwapId
+		);
+
+		// selling the wanted kitty voids the swap
+		assert_ok!(KittiesModule::propose_swap(
+			RuntimeOrigin::signed(charlie),
+			kitty_a,
+			kitty_b,
+			None
+		));
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(bob), kitty_b));
+		assert_ok!(KittiesModule::buy(RuntimeOrigin::signed(alice), kitty_b));
+		System::assert_has_event(Event::SwapInvalidated { swap_id: 1 }.into());
+		assert_eq!(KittiesModule::swaps(1), None);
+		assert_eq!(Balances::reserved_balance(charlie), 0);
+	});
+}
+
+#[test]
+fn open_swaps_are_capped_per_kitty() {
+	build_and_execute(|| {
+		let (alice, bob, charlie) = (1, 2, 3);
+		for account_id in [alice, bob, charlie] {
+			Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		}
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(alice), *b"abcdabcd"));
+		for _ in 0..3 {
+			assert_ok!(KittiesModule::create(RuntimeOrigin::signed(bob), *b"abcdabcd"));
+		}
+		for _ in 0..2 {
+			assert_ok!(KittiesModule::create(RuntimeOrigin::signed(charlie), *b"abcdabcd"));
+		}
+
+		// kitty 0 is offered in as many swaps as it can be
+		assert_ok!(KittiesModule::propose_swap(RuntimeOrigin::signed(alice), 0, 1, None));
+		assert_ok!(KittiesModule::propose_swap(RuntimeOrigin::signed(alice), 0, 2, None));
+		assert_noop!(
+			KittiesModule::propose_swap(RuntimeOrigin::signed(alice), 0, 3, None),
+			Error::<Test>::TooManySwaps
+		);
+
+		// kitty 1 is wanted in as many swaps as it can be
+		assert_ok!(KittiesModule::propose_swap(RuntimeOrigin::signed(charlie), 4, 1, None));
+		assert_noop!(
+			KittiesModule::propose_swap(RuntimeOrigin::signed(charlie), 5, 1, None),
+			Error::<Test>::TooManySwaps
+		);
+		assert_eq!(Balances::reserved_balance(alice), 200);
+		assert_eq!(Balances::reserved_balance(charlie), 100);
+
+		// closing a swap makes room again
+		assert_ok!(KittiesModule::cancel_swap(RuntimeOrigin::signed(alice), 0));
+		assert_ok!(KittiesModule::propose_swap(RuntimeOrigin::signed(alice), 0, 3, None));
+		assert_ok!(KittiesModule::propose_swap(RuntimeOrigin::signed(charlie), 5, 1, None));
+	});
+}
+
+#[test]
+fn swap_proposer_must_afford_the_deposit() {
+	build_and_execute(|| {
+		let (alice, bob) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), alice, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), bob, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(alice), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(bob), *b"abcdabcd"));
+
+		let free = Balances::free_balance(alice);
+		assert_ok!(Balances::reserve(&alice, free));
+		assert_noop!(
+			KittiesModule::propose_swap(RuntimeOrigin::signed(alice), 0, 1, None),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+	});
+}
+
+#[test]
+fn it_works_for_lend() {
+	build_and_execute(|| {
+		let kitty_id = 0;
+		let (owner, borrower) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+
+		//kitty not exist
+		assert_noop!(
+			KittiesModule::lend(RuntimeOrigin::signed(owner), kitty_id, borrower, 10),
+			Error::<Test>::InvalidKittyId
+		);
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+
+		assert_noop!(
+			KittiesModule::lend(RuntimeOrigin::signed(borrower), kitty_id, borrower, 10),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			KittiesModule::lend(RuntimeOrigin::signed(owner), kitty_id, borrower, 1),
+			Error::<Test>::InvalidLoanPeriod
+		);
+
+		//success
+		assert_ok!(KittiesModule::lend(RuntimeOrigin::signed(owner), kitty_id, borrower, 10));
+		assert_eq!(KittiesModule::kitty_loans(kitty_id), Some(crate::Loan { borrower, until: 10 }));
+		assert_eq!(KittiesModule::kitty_user(kitty_id), Some(borrower));
+		assert_eq!(KittiesModule::kitty_owner(kitty_id), Some(owner));
+		System::assert_last_event(
+			Event::KittyLent { who: owner, borrower, kitty_id, until: 10 }.into(),
+		);
+
+		assert_noop!(
+			KittiesModule::lend(RuntimeOrigin::signed(owner), kitty_id, 3, 10),
+			Error::<Test>::KittyOnLoan
+		);
+	});
+}
+
+#[test]
+fn lent_kitty_cannot_change_hands() {
+	build_and_execute(|| {
+		let (kitty_id, other_kitty_id) = (0, 1);
+		let (owner, borrower) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), borrower, 1_000_000_000, 0).unwrap();
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(borrower), *b"abcdabcd"));
+		assert_ok!(KittiesModule::lend(RuntimeOrigin::signed(owner), kitty_id, borrower, 10));
+
+		assert_noop!(
+			KittiesModule::transfer(RuntimeOrigin::signed(owner), borrower, kitty_id),
+			Error::<Test>::KittyOnLoan
+		);
+		assert_noop!(
+			KittiesModule::sale(RuntimeOrigin::signed(owner), kitty_id),
+			Error::<Test>::KittyOnLoan
+		);
+		assert_noop!(
+			KittiesModule::propose_swap(
+				RuntimeOrigin::signed(borrower),
+				other_kitty_id,
+				kitty_id,
+				None
+			),
+			Error::<Test>::KittyOnLoan
+		);
+	});
+}
+
+#[test]
+fn only_the_user_of_both_parents_can_breed() {
+	build_and_execute(|| {
+		let (owner, borrower, stranger) = (1, 2, 3);
+		for account_id in [owner, borrower, stranger] {
+			Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		}
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(borrower), *b"abcdabcd"));
+
+		// a stranger can't breed kitties it neither owns nor borrows
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(stranger), 0, 1, *b"abcdabcd"),
+			refunded(Error::<Test>::NotOwner)
+		);
+
+		// the borrower breeds the lent kitty with its own, the lender can't during the loan
+		assert_ok!(KittiesModule::lend(RuntimeOrigin::signed(owner), 0, borrower, 10));
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(borrower), 0, 2, *b"abcdabcd"));
+		assert_eq!(KittiesModule::kitty_owner(3), Some(borrower));
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(owner), 0, 1, *b"abcdabcd"),
+			refunded(Error::<Test>::NotOwner)
+		);
+		// both parents must be used by the breeder
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(borrower), 0, 1, *b"abcdabcd"),
+			refunded(Error::<Test>::NotOwner)
+		);
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(stranger), 0, 2, *b"abcdabcd"),
+			refunded(Error::<Test>::NotOwner)
+		);
+
+		// the lender breeds again once the loan ends
+		run_to_block(10);
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(owner), 0, 1, *b"abcdabcd"));
+	});
+}
+
+#[test]
+fn loans_end_automatically() {
+	build_and_execute(|| {
+		let kitty_id = 0;
+		let (owner, borrower) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+
+		for _ in 0..3 {
+			assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		}
+		assert_ok!(KittiesModule::lend(RuntimeOrigin::signed(owner), kitty_id, borrower, 5));
+		assert_ok!(KittiesModule::lend(RuntimeOrigin::signed(owner), kitty_id + 1, borrower, 5));
+		// the expiry queue of a block is bounded
+		assert_noop!(
+			KittiesModule::lend(RuntimeOrigin::signed(owner), kitty_id + 2, borrower, 5),
+			Error::<Test>::TooManyLoans
+		);
+
+		run_to_block(4);
+		assert_eq!(KittiesModule::kitty_user(kitty_id), Some(borrower));
+
+		run_to_block(5);
+		assert_eq!(KittiesModule::kitty_loans(kitty_id), None);
+		assert_eq!(KittiesModule::kitty_user(kitty_id), Some(owner));
+		System::assert_has_event(Event::KittyLoanEnded { borrower, kitty_id }.into());
+
+		assert_ok!(KittiesModule::transfer(RuntimeOrigin::signed(owner), borrower, kitty_id));
+	});
+}
+
+#[test]
+fn it_works_for_lock() {
+	build_and_execute(|| {
+		let (kitty_id, other_kitty_id) = (0, 1);
+		let (owner, another_account_id) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), another_account_id, 1_000_000_000, 0).unwrap();
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+
+		assert_noop!(
+			KittiesModule::lock(RuntimeOrigin::signed(another_account_id), kitty_id),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			KittiesModule::unlock(RuntimeOrigin::signed(owner), kitty_id),
+			Error::<Test>::NotLocked
+		);
+
+		//success
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(owner), other_kitty_id));
+		assert_ok!(KittiesModule::lock(RuntimeOrigin::signed(owner), kitty_id));
+		assert_ok!(KittiesModule::lock(RuntimeOrigin::signed(owner), other_kitty_id));
+		assert_eq!(KittiesModule::kitty_locks(kitty_id), Some(LockReason::Owner));
+		System::assert_last_event(
+			Event::KittyLocked { kitty_id: other_kitty_id, reason: LockReason::Owner }.into(),
+		);
+
+		// locked kitties can't be transferred, sold, bought or bred
+		assert_noop!(
+			KittiesModule::transfer(RuntimeOrigin::signed(owner), another_account_id, kitty_id),
+			Error::<Test>::KittyLocked
+		);
+		assert_noop!(
+			KittiesModule::sale(RuntimeOrigin::signed(owner), kitty_id),
+			Error::<Test>::KittyLocked
+		);
+		assert_noop!(
+			KittiesModule::buy(RuntimeOrigin::signed(another_account_id), other_kitty_id),
+			Error::<Test>::KittyLocked
+		);
+		assert_noop!(
+			KittiesModule::breed(
+				RuntimeOrigin::signed(owner),
+				kitty_id,
+				other_kitty_id,
+				*b"abcdabcd"
+			),
+			refunded(Error::<Test>::KittyLocked)
+		);
+
+		assert_noop!(
+			KittiesModule::unlock(RuntimeOrigin::signed(another_account_id), kitty_id),
+			Error::<Test>::NotOwner
+		);
+		assert_ok!(KittiesModule::unlock(RuntimeOrigin::signed(owner), kitty_id));
+		assert_eq!(KittiesModule::kitty_locks(kitty_id), None);
+		System::assert_last_event(Event::KittyUnlocked { kitty_id }.into());
+		assert_ok!(KittiesModule::transfer(
+			RuntimeOrigin::signed(owner),
+			another_account_id,
+			kitty_id
+		));
+	});
+}
+
+#[test]
+fn it_works_for_force_freeze() {
+	build_and_execute(|| {
+		let kitty_id = 0;
+		let owner = 1;
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+
+		assert_noop!(
+			KittiesModule::force_freeze(RuntimeOrigin::root(), kitty_id),
+			Error::<Test>::InvalidKittyId
+		);
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		assert_ok!(KittiesModule::lock(RuntimeOrigin::signed(owner), kitty_id));
+
+		assert_noop!(
+			KittiesModule::force_freeze(RuntimeOrigin::signed(owner), kitty_id),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		//success, the freeze replaces the owner's lock
+		assert_ok!(KittiesModule::force_freeze(RuntimeOrigin::root(), kitty_id));
+		assert_eq!(KittiesModule::kitty_locks(kitty_id), Some(LockReason::Frozen));
+		System::assert_last_event(
+			Event::KittyLocked { kitty_id, reason: LockReason::Frozen }.into(),
+		);
+
+		// only the freeze origin can lift a freeze
+		assert_noop!(
+			KittiesModule::unlock(RuntimeOrigin::signed(owner), kitty_id),
+			Error::<Test>::KittyLocked
+		);
+		assert_ok!(KittiesModule::unlock(RuntimeOrigin::root(), kitty_id));
+		assert_eq!(KittiesModule::kitty_locks(kitty_id), None);
+	});
+}
+
+#[test]
+fn it_works_for_force_transfer() {
+	build_and_execute(|| {
+		let (kitty_id, other_kitty_id) = (0, 1);
+		let (owner, thief, borrower) = (1, 2, 3);
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), thief, 1_000_000_000, 0).unwrap();
+
+		assert_noop!(
+			KittiesModule::force_transfer(RuntimeOrigin::root(), kitty_id, owner),
+			Error::<Test>::InvalidKittyId
+		);
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(thief), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		assert_ok!(KittiesModule::propose_swap(
+			RuntimeOrigin::signed(thief),
+			kitty_id,
+			other_kitty_id,
+			None
+		));
+		assert_ok!(KittiesModule::lend(RuntimeOrigin::signed(thief), kitty_id, borrower, 10));
+		assert_ok!(KittiesModule::lock(RuntimeOrigin::signed(thief), kitty_id));
+
+		assert_noop!(
+			KittiesModule::force_transfer(RuntimeOrigin::signed(owner), kitty_id, owner),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		//success
+		assert_ok!(KittiesModule::force_transfer(RuntimeOrigin::root(), kitty_id, owner));
+		assert_eq!(KittiesModule::kitty_owner(kitty_id), Some(owner));
+		assert_eq!(KittiesModule::kitty_locks(kitty_id), None);
+		assert_eq!(KittiesModule::kitty_loans(kitty_id), None);
+		assert_eq!(KittiesModule::swaps(0), None);
+		System::assert_last_event(
+			Event::KittyForceTransferred { kitty_id, from: thief, to: owner }.into(),
+		);
+	});
+}
+
+#[test]
+fn it_works_for_force_burn() {
+	build_and_execute(|| {
+		let kitty_id = 0;
+		let owner = 1;
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+
+		assert_noop!(
+			KittiesModule::force_burn(RuntimeOrigin::root(), kitty_id),
+			Error::<Test>::InvalidKittyId
+		);
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		assert_ok!(KittiesModule::breed(
+			RuntimeOrigin::signed(owner),
+			kitty_id,
+			kitty_id + 1,
+			*b"abcdabcd"
+		));
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(owner), kitty_id));
+
+		assert_noop!(
+			KittiesModule::force_burn(RuntimeOrigin::signed(owner), kitty_id),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		//success
+		assert_ok!(KittiesModule::force_burn(RuntimeOrigin::root(), kitty_id));
+		assert_eq!(KittiesModule::kitties(kitty_id), None);
+		assert_eq!(KittiesModule::kitty_owner(kitty_id), None);
+		assert_eq!(KittiesModule::kitty_on_sale(kitty_id), None);
+		// the child keeps its lineage
+		assert_eq!(KittiesModule::kitty_parents(kitty_id + 2), Some((kitty_id, kitty_id + 1)));
+		System::assert_last_event(Event::KittyBurned { kitty_id, owner }.into());
+	});
+}
+
+#[test]
+fn it_works_for_force_set_dna() {
+	build_and_execute(|| {
+		let kitty_id = 0;
+		let owner = 1;
+		let dna = [7u8; 16];
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+
+		assert_noop!(
+			KittiesModule::force_set_dna(RuntimeOrigin::root(), kitty_id, dna),
+			Error::<Test>::InvalidKittyId
+		);
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+
+		assert_noop!(
+			KittiesModule::force_set_dna(RuntimeOrigin::signed(owner), kitty_id, dna),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		//success
+		assert_ok!(KittiesModule::force_set_dna(RuntimeOrigin::root(), kitty_id, dna));
+		assert_eq!(KittiesModule::kitties(kitty_id).map(|kitty| kitty.dna), Some(dna));
+		System::assert_last_event(Event::KittyDnaSet { kitty_id, dna }.into());
+	});
+}
+
+#[test]
+fn it_works_for_force_delist() {
+	build_and_execute(|| {
+		let kitty_id = 0;
+		let owner = 1;
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+
+		assert_noop!(
+			KittiesModule::force_delist(RuntimeOrigin::root(), kitty_id),
+			Error::<Test>::NotOnSale
+		);
+
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(owner), kitty_id));
+
+		assert_noop!(
+			KittiesModule::force_delist(RuntimeOrigin::signed(owner), kitty_id),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		//success
+		assert_ok!(KittiesModule::force_delist(RuntimeOrigin::root(), kitty_id));
+		assert_eq!(KittiesModule::kitty_on_sale(kitty_id), None);
+		System::assert_last_event(Event::KittyDelisted { kitty_id }.into());
+	});
+}
+
+#[test]
+fn supply_is_capped() {
+	build_and_execute(|| {
+		let account_id = 1;
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+
+		assert_eq!(KittiesModule::remaining_supply(), 100);
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_eq!(KittiesModule::remaining_supply(), 98);
+
+		crate::NextKittyId::<Test>::set(100);
+		assert_eq!(KittiesModule::remaining_supply(), 0);
+		assert_noop!(
+			KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"),
+			Error::<Test>::SupplyCapReached
+		);
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(account_id), 0, 1, *b"abcdabcd"),
+			refunded(Error::<Test>::SupplyCapReached)
+		);
+	});
+}
+
+#[test]
+fn mints_are_limited_per_account_and_period() {
+	build_and_execute(|| {
+		let (account_id, another_account_id) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), another_account_id, 1_000_000_000, 0).unwrap();
+
+		for _ in 0..5 {
+			assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		}
+		assert_noop!(
+			KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"),
+			Error::<Test>::MintLimitReached
+		);
+		// the allowance is per account
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(another_account_id), *b"abcdabcd"));
+		// breeding doesn't count as minting
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(account_id), 0, 1, *b"abcdabcd"));
+
+		// the allowance is restored in the next period
+		run_to_block(10);
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_eq!(KittiesModule::account_mints(account_id), (1, 1));
+	});
+}
+
+#[test]
+fn it_works_for_set_mint_window() {
+	build_and_execute(|| {
+		let account_id = 1;
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+
+		assert_noop!(
+			KittiesModule::set_mint_window(RuntimeOrigin::signed(account_id), Some(5), Some(10)),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		//success
+		assert_ok!(KittiesModule::set_mint_window(RuntimeOrigin::root(), Some(5), Some(10)));
+		assert_eq!(KittiesModule::mint_window(), (Some(5), Some(10)));
+		System::assert_last_event(Event::MintWindowSet { open: Some(5), close: Some(10) }.into());
+
+		// not open yet
+		assert_noop!(
+			KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"),
+			Error::<Test>::MintWindowClosed
+		);
+		run_to_block(5);
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		// already closed
+		run_to_block(10);
+		assert_noop!(
+			KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"),
+			Error::<Test>::MintWindowClosed
+		);
+	});
+}
+
+#[test]
+fn it_works_for_set_price_curve() {
+	build_and_execute(|| {
+		let account_id = 1;
+		let params = CurveParams { rate: Perbill::from_percent(10) };
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+
+		// without curve parameters every kitty costs the base price
+		assert_eq!(KittiesModule::mint_price(), MintPrice::get());
+		assert_eq!(KittiesModule::breed_price(), BreedPrice::get());
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_eq!(KittiesModule::mint_price(), MintPrice::get());
+
+		assert_noop!(
+			KittiesModule::set_price_curve(RuntimeOrigin::signed(account_id), params),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		//success
+		assert_ok!(KittiesModule::set_price_curve(RuntimeOrigin::root(), params));
+		assert_eq!(KittiesModule::price_curve_params(), params);
+		System::assert_last_event(Event::PriceCurveSet { params }.into());
+
+		// one kitty in existence adds 10% to the base price
+		let price = MintPrice::get() + MintPrice::get() / 10;
+		assert_eq!(KittiesModule::mint_price(), price);
+		let balance = Balances::free_balance(account_id);
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_eq!(Balances::free_balance(account_id), balance - price);
+		assert_eq!(KittiesModule::mint_price(), MintPrice::get() + MintPrice::get() / 5);
+		assert_eq!(KittiesModule::breed_price(), BreedPrice::get() + BreedPrice::get() / 5);
+	});
+}
+
+#[test]
+fn price_curves_follow_supply() {
+	let params = CurveParams { rate: Perbill::from_percent(10) };
+
+	assert_eq!(<LinearCurve as PriceCurve<u128>>::price(1_000, 0, &params), 1_000);
+	assert_eq!(<LinearCurve as PriceCurve<u128>>::price(1_000, 10, &params), 2_000);
+	assert_eq!(<ExponentialCurve as PriceCurve<u128>>::price(1_000, 0, &params), 1_000);
+	assert_eq!(<ExponentialCurve as PriceCurve<u128>>::price(1_000, 2, &params), 1_210);
+	// both curves saturate instead of overflowing
+	assert_eq!(<LinearCurve as PriceCurve<u128>>::price(u128::MAX, 10, &params), u128::MAX);
+	assert_eq!(<ExponentialCurve as PriceCurve<u128>>::price(u128::MAX, 2, &params), u128::MAX);
+}
+
+#[test]
+fn breed_charges_breed_price() {
+	build_and_execute(|| {
+		let account_id = 1;
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+
+		let balance = Balances::free_balance(account_id);
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_eq!(Balances::free_balance(account_id), balance - 2 * MintPrice::get());
+
+		let balance = Balances::free_balance(account_id);
+		let post_info =
+			KittiesModule::breed(RuntimeOrigin::signed(account_id), 0, 1, *b"abcdabcd").unwrap();
+		assert_eq!(post_info.pays_fee, Pays::Yes);
+		assert_eq!(Balances::free_balance(account_id), balance - BreedPrice::get());
+	});
+}
+
+#[test]
+fn failed_validations_leave_balances_unchanged() {
+	build_and_execute(|| {
+		let (account_id, poor_account_id) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), poor_account_id, EXISTENTIAL_DEPOSIT, 0)
+			.unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_ok!(KittiesModule::lock(RuntimeOrigin::signed(account_id), 1));
+
+		let pallet_account_id: u64 = KittyPalletId::get().into_account_truncating();
+		let balance = Balances::free_balance(account_id);
+		let pallet_balance = Balances::free_balance(pallet_account_id);
+		let assert_balances_unchanged = || {
+			assert_eq!(Balances::free_balance(account_id), balance);
+			assert_eq!(Balances::free_balance(pallet_account_id), pallet_balance);
+		};
+
+		for (parents, error) in [
+			((0, 2), Error::<Test>::InvalidKittyId),
+			((0, 0), Error::<Test>::SameKittyId),
+			((0, 1), Error::<Test>::KittyLocked),
+		] {
+			assert_noop!(
+				KittiesModule::breed(
+					RuntimeOrigin::signed(account_id),
+					parents.0,
+					parents.1,
+					*b"abcdabcd"
+				),
+				refunded(error)
+			);
+			assert_balances_unchanged();
+		}
+
+		assert_ok!(KittiesModule::unlock(RuntimeOrigin::signed(account_id), 1));
+		crate::NextKittyId::<Test>::set(100);
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(account_id), 0, 1, *b"abcdabcd"),
+			refunded(Error::<Test>::SupplyCapReached)
+		);
+		assert_noop!(
+			KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"),
+			Error::<Test>::SupplyCapReached
+		);
+		assert_balances_unchanged();
+
+		// breeding someone else's kitties is refunded too
+		crate::NextKittyId::<Test>::set(2);
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(poor_account_id), 0, 1, *b"abcdabcd"),
+			refunded(Error::<Test>::NotOwner)
+		);
+		assert_balances_unchanged();
+
+		// failing to pay is charged for like any other dispatch error
+		assert_ok!(KittiesModule::lend(RuntimeOrigin::signed(account_id), 0, poor_account_id, 10));
+		assert_ok!(KittiesModule::lend(RuntimeOrigin::signed(account_id), 1, poor_account_id, 10));
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(poor_account_id), 0, 1, *b"abcdabcd"),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+		assert_balances_unchanged();
+	});
+}
+
+#[test]
+fn it_works_for_create_collection_and_mint() {
+	build_and_execute(|| {
+		let (owner_id, account_id) = (1, 2);
+		let collection_id = 1;
+		Balances::set_balance(RuntimeOrigin::root(), owner_id, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+
+		assert_noop!(
+			KittiesModule::mint(RuntimeOrigin::signed(account_id), collection_id, *b"abcdabcd"),
+			Error::<Test>::InvalidCollectionId
+		);
+
+		//success
+		assert_ok!(KittiesModule::create_collection(
+			RuntimeOrigin::signed(owner_id),
+			b"species".to_vec().try_into().unwrap(),
+			500,
+			2
+		));
+		assert_eq!(KittiesModule::next_collection_id(), collection_id + 1);
+		System::assert_last_event(
+			Event::CollectionCreated {
+				who: owner_id,
+				collection_id,
+				mint_price: 500,
+				max_supply: 2,
+			}
+			.into(),
+		);
+
+		// create still mints into the default collection, sharing the global ids
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_eq!(KittiesModule::kitty_collection(0), crate::DEFAULT_COLLECTION);
+
+		let owner_balance = Balances::free_balance(owner_id);
+		assert_ok!(KittiesModule::mint(
+			RuntimeOrigin::signed(account_id),
+			collection_id,
+			*b"abcdabcd"
+		));
+		assert_eq!(Balances::free_balance(owner_id), owner_balance + 500);
+		assert_eq!(KittiesModule::kitty_owner(1), Some(account_id));
+		assert_eq!(KittiesModule::kitty_collection(1), collection_id);
+		assert!(crate::CollectionKitties::<Test>::contains_key(collection_id, 1));
+		System::assert_last_event(
+			Event::KittyCreated {
+				who: account_id,
+				collection_id,
+				kitty_id: 1,
+				kitty: KittiesModule::kitties(1).unwrap(),
+			}
+			.into(),
+		);
+
+		assert_ok!(KittiesModule::mint(
+			RuntimeOrigin::signed(account_id),
+			collection_id,
+			*b"abcdabcd"
+		));
+		assert_eq!(KittiesModule::collections(collection_id).unwrap().supply, 2);
+		assert_noop!(
+			KittiesModule::mint(RuntimeOrigin::signed(account_id), collection_id, *b"abcdabcd"),
+			Error::<Test>::SupplyCapReached
+		);
+	});
+}
+
+#[test]
+fn mint_respects_the_mint_window() {
+	build_and_execute(|| {
+		let (owner_id, account_id) = (1, 2);
+		let collection_id = 1;
+		Balances::set_balance(RuntimeOrigin::root(), owner_id, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create_collection(
+			RuntimeOrigin::signed(owner_id),
+			Default::default(),
+			500,
+			10
+		));
+		assert_ok!(KittiesModule::set_mint_window(RuntimeOrigin::root(), Some(5), Some(10)));
+
+		// not open yet
+		assert_noop!(
+			KittiesModule::mint(RuntimeOrigin::signed(account_id), collection_id, *b"abcdabcd"),
+			Error::<Test>::MintWindowClosed
+		);
+		run_to_block(5);
+		assert_ok!(KittiesModule::mint(
+			RuntimeOrigin::signed(account_id),
+			collection_id,
+			*b"abcdabcd"
+		));
+		// already closed
+		run_to_block(10);
+		assert_noop!(
+			KittiesModule::mint(RuntimeOrigin::signed(account_id), collection_id, *b"abcdabcd"),
+			Error::<Test>::MintWindowClosed
+		);
+	});
+}
+
+#[test]
+fn mint_counts_against_the_mint_allowance() {
+	build_and_execute(|| {
+		let (owner_id, account_id) = (1, 2);
+		let collection_id = 1;
+		Balances::set_balance(RuntimeOrigin::root(), owner_id, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create_collection(
+			RuntimeOrigin::signed(owner_id),
+			Default::default(),
+			500,
+			10
+		));
+
+		// minting into a collection and creating share the allowance
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		for _ in 0..4 {
+			assert_ok!(KittiesModule::mint(
+				RuntimeOrigin::signed(account_id),
+				collection_id,
+				*b"abcdabcd"
+			));
+		}
+		assert_eq!(KittiesModule::account_mints(account_id), (0, 5));
+		assert_noop!(
+			KittiesModule::mint(RuntimeOrigin::signed(account_id), collection_id, *b"abcdabcd"),
+			Error::<Test>::MintLimitReached
+		);
+		assert_noop!(
+			KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"),
+			Error::<Test>::MintLimitReached
+		);
+
+		// the allowance is restored in the next period
+		run_to_block(10);
+		assert_ok!(KittiesModule::mint(
+			RuntimeOrigin::signed(account_id),
+			collection_id,
+			*b"abcdabcd"
+		));
+	});
+}
+
+#[test]
+fn mint_follows_the_price_curve() {
+	build_and_execute(|| {
+		let (owner_id, account_id) = (1, 2);
+		let collection_id = 1;
+		let params = CurveParams { rate: Perbill::from_percent(10) };
+		Balances::set_balance(RuntimeOrigin::root(), owner_id, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create_collection(
+			RuntimeOrigin::signed(owner_id),
+			Default::default(),
+			500,
+			10
+		));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_ok!(KittiesModule::set_price_curve(RuntimeOrigin::root(), params));
+
+		// kitties outside the collection don't count towards its supply
+		let collection = KittiesModule::collections(collection_id).unwrap();
+		assert_eq!(KittiesModule::collection_mint_price(&collection), 500);
+		let owner_balance = Balances::free_balance(owner_id);
+		let balance = Balances::free_balance(account_id);
+		assert_ok!(KittiesModule::mint(
+			RuntimeOrigin::signed(account_id),
+			collection_id,
+			*b"abcdabcd"
+		));
+		assert_eq!(Balances::free_balance(owner_id), owner_balance + 500);
+		assert_eq!(Balances::free_balance(account_id), balance - 500);
+
+		// each kitty minted into it adds 10% to the collection's mint price
+		let collection = KittiesModule::collections(collection_id).unwrap();
+		assert_eq!(KittiesModule::collection_mint_price(&collection), 550);
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_eq!(KittiesModule::collection_mint_price(&collection), 550);
+	});
+}
+
+#[test]
+fn breeding_stays_within_a_collection() {
+	build_and_execute(|| {
+		let account_id = 1;
+		let collection_id = 1;
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create_collection(
+			RuntimeOrigin::signed(account_id),
+			Default::default(),
+			0,
+			3
+		));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_ok!(KittiesModule::mint(
+			RuntimeOrigin::signed(account_id),
+			collection_id,
+			*b"abcdabcd"
+		));
+		assert_ok!(KittiesModule::mint(
+			RuntimeOrigin::signed(account_id),
+			collection_id,
+			*b"abcdabcd"
+		));
+
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(account_id), 0, 1, *b"abcdabcd"),
+			refunded(Error::<Test>::CollectionMismatch)
+		);
+
+		//success
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(account_id), 1, 2, *b"abcdabcd"));
+		assert_eq!(KittiesModule::kitty_collection(3), collection_id);
+		assert_eq!(KittiesModule::collections(collection_id).unwrap().supply, 3);
+
+		// bred kitties count against the collection's supply
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(account_id), 1, 2, *b"abcdabcd"),
+			refunded(Error::<Test>::SupplyCapReached)
+		);
+	});
+}
+
+#[test]
+fn migration_places_kitties_into_default_collection() {
+	build_and_execute(|| {
+		let account_id = 1;
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+
+		// simulate a chain that predates collections
+		let _ = crate::KittyCollection::<Test>::clear(u32::MAX, None);
+		let _ = crate::CollectionKitties::<Test>::clear(u32::MAX, None);
+		StorageVersion::new(2).put::<KittiesModule>();
+
+		crate::migrations::v3::migrate::<Test>();
+
+		assert_eq!(KittiesModule::on_chain_storage_version(), 3);
+		for kitty_id in 0..2 {
+			assert!(crate::KittyCollection::<Test>::contains_key(kitty_id));
+			assert!(crate::CollectionKitties::<Test>::contains_key(
+				crate::DEFAULT_COLLECTION,
+				kitty_id
+			));
+			assert_eq!(KittiesModule::kitty_owner(kitty_id), Some(account_id));
+		}
+		assert_eq!(KittiesModule::next_kitty_id(), 2);
+	});
+}
+
+#[test]
+fn it_works_for_set_and_clear_metadata() {
+	build_and_execute(|| {
+		let (account_id, recipient_id) = (1, 2);
+		let kitty_id = 0;
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), recipient_id, 1_000_000_000, 0).unwrap();
+		let uri: crate::MetadataOf<Test> = b"ipfs://bafykitty".to_vec().try_into().unwrap();
+
+		assert_noop!(
+			KittiesModule::set_metadata(RuntimeOrigin::signed(account_id), kitty_id, uri.clone()),
+			Error::<Test>::InvalidKittyId
+		);
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_noop!(
+			KittiesModule::set_metadata(RuntimeOrigin::signed(recipient_id), kitty_id, uri.clone()),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			KittiesModule::clear_metadata(RuntimeOrigin::signed(account_id), kitty_id),
+			Error::<Test>::NoMetadata
+		);
+
+		//success
+		assert_ok!(KittiesModule::set_metadata(
+			RuntimeOrigin::signed(account_id),
+			kitty_id,
+			uri.clone()
+		));
+		assert_eq!(Balances::reserved_balance(account_id), 16 * 10);
+		assert_eq!(KittiesModule::kitty_metadata(kitty_id).unwrap().0, uri);
+		System::assert_last_event(Event::MetadataSet { kitty_id, data: uri.clone() }.into());
+
+		// replacing the metadata only keeps the deposit for the new bytes
+		assert_ok!(KittiesModule::set_metadata(
+			RuntimeOrigin::signed(account_id),
+			kitty_id,
+			b"ipfs://cat".to_vec().try_into().unwrap()
+		));
+		assert_eq!(Balances::reserved_balance(account_id), 10 * 10);
+
+		// the deposit goes back to whoever paid it, even after the kitty changed hands
+		assert_ok!(KittiesModule::transfer(
+			RuntimeOrigin::signed(account_id),
+			recipient_id,
+			kitty_id
+		));
+		assert_ok!(KittiesModule::clear_metadata(RuntimeOrigin::signed(recipient_id), kitty_id));
+		assert_eq!(Balances::reserved_balance(account_id), 0);
+		assert_eq!(KittiesModule::kitty_metadata(kitty_id), None);
+		System::assert_last_event(Event::MetadataCleared { kitty_id }.into());
+	});
+}
+
+#[test]
+fn it_works_for_set_and_clear_attribute() {
+	build_and_execute(|| {
+		let account_id = 1;
+		let kitty_id = 0;
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		let key: crate::AttributeKeyOf<Test> = b"eyes".to_vec().try_into().unwrap();
+		let value: crate::AttributeValueOf<Test> = b"green".to_vec().try_into().unwrap();
+
+		//success
+		assert_ok!(KittiesModule::set_attribute(
+			RuntimeOrigin::signed(account_id),
+			kitty_id,
+			key.clone(),
+			value.clone()
+		));
+		assert_eq!(Balances::reserved_balance(account_id), 9 * 10);
+		System::assert_last_event(
+			Event::AttributeSet { kitty_id, key: key.clone(), value: value.clone() }.into(),
+		);
+
+		let info = KittiesModule::kitty_info(kitty_id).unwrap();
+		assert_eq!(info.owner, account_id);
+		assert_eq!(info.metadata, Vec::<u8>::new());
+		assert_eq!(info.attributes, vec![(b"eyes".to_vec(), b"green".to_vec())]);
+
+		assert_ok!(KittiesModule::clear_attribute(
+			RuntimeOrigin::signed(account_id),
+			kitty_id,
+			key.clone()
+		));
+		assert_eq!(Balances::reserved_balance(account_id), 0);
+		assert_noop!(
+			KittiesModule::clear_attribute(RuntimeOrigin::signed(account_id), kitty_id, key),
+			Error::<Test>::NoAttribute
+		);
+
+		// burning a kitty returns the deposits held for it
+		assert_ok!(KittiesModule::set_metadata(
+			RuntimeOrigin::signed(account_id),
+			kitty_id,
+			b"ipfs://cat".to_vec().try_into().unwrap()
+		));
+		assert_ok!(KittiesModule::set_attribute(
+			RuntimeOrigin::signed(account_id),
+			kitty_id,
+			b"eyes".to_vec().try_into().unwrap(),
+			value
+		));
+		assert_ok!(KittiesModule::force_burn(RuntimeOrigin::root(), kitty_id));
+		assert_eq!(Balances::reserved_balance(account_id), 0);
+		assert_eq!(KittiesModule::kitty_info(kitty_id), None);
+		assert_eq!(KittiesModule::kitty_attribute_count(kitty_id), 0);
+	});
+}
+
+#[test]
+fn attributes_are_capped_per_kitty() {
+	build_and_execute(|| {
+		let account_id = 1;
+		let kitty_id = 0;
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		let set_attribute = |key: &[u8]| {
+			KittiesModule::set_attribute(
+				RuntimeOrigin::signed(account_id),
+				kitty_id,
+				key.to_vec().try_into().unwrap(),
+				b"green".to_vec().try_into().unwrap(),
+			)
+		};
+
+		assert_ok!(set_attribute(b"eyes"));
+		assert_ok!(set_attribute(b"tail"));
+		assert_eq!(KittiesModule::kitty_attribute_count(kitty_id), 2);
+		assert_noop!(set_attribute(b"paws"), Error::<Test>::TooManyAttributes);
+		// replacing an attribute doesn't add to the count
+		assert_ok!(set_attribute(b"eyes"));
+		assert_eq!(KittiesModule::kitty_attribute_count(kitty_id), 2);
+
+		// clearing an attribute makes room again
+		assert_ok!(KittiesModule::clear_attribute(
+			RuntimeOrigin::signed(account_id),
+			kitty_id,
+			b"tail".to_vec().try_into().unwrap()
+		));
+		assert_eq!(KittiesModule::kitty_attribute_count(kitty_id), 1);
+		assert_ok!(set_attribute(b"paws"));
+	});
+}
+
+#[test]
+fn levels_follow_thresholds() {
+	let thresholds = [10, 30, 60];
+	assert_eq!(level_for(0, &thresholds), 1);
+	assert_eq!(level_for(9, &thresholds), 1);
+	assert_eq!(level_for(10, &thresholds), 2);
+	assert_eq!(level_for(59, &thresholds), 3);
+	assert_eq!(level_for(u32::MAX, &thresholds), 4);
+	assert_eq!(level_for(u32::MAX, &[]), 1);
+}
+
+#[test]
+fn higher_levels_pass_on_more_genes() {
+	let (dna_1, dna_2) = ([1u8; 16], [2u8; 16]);
+	let random: [u8; 16] = core::array::from_fn(|i| (i * 16) as u8);
+	let genes_from_1 = |level_1, level_2| {
+		inherit_dna((dna_1, level_1), (dna_2, level_2), random)
+			.iter()
+			.filter(|gene| **gene == 1)
+			.count()
+	};
+
+	// equal levels split the genes evenly
+	assert_eq!(genes_from_1(1, 1), 8);
+	assert_eq!(genes_from_1(3, 1), 12);
+	assert_eq!(genes_from_1(1, 3), 4);
+	assert_eq!(genes_from_1(0, 0), 8);
+}
+
+#[test]
+fn it_works_for_feed() {
+	build_and_execute(|| {
+		let kitty_id = 0;
+		let (owner, borrower) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), borrower, 1_000_000_000, 0).unwrap();
+
+		assert_noop!(
+			KittiesModule::feed(RuntimeOrigin::signed(owner), kitty_id),
+			Error::<Test>::InvalidKittyId
+		);
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		assert_eq!(KittiesModule::kitty_progress(kitty_id), Some(Progress::default()));
+		assert_noop!(
+			KittiesModule::feed(RuntimeOrigin::signed(borrower), kitty_id),
+			Error::<Test>::NotOwner
+		);
+
+		//success
+		let balance = Balances::free_balance(owner);
+		assert_ok!(KittiesModule::feed(RuntimeOrigin::signed(owner), kitty_id));
+		assert_eq!(Balances::free_balance(owner), balance - FeedFee::get());
+		assert_eq!(
+			KittiesModule::kitty_progress(kitty_id),
+			Some(Progress { experience: 10, level: 2 })
+		);
+		System::assert_has_event(Event::KittyLevelUp { kitty_id, level: 2 }.into());
+		System::assert_last_event(Event::KittyFed { who: owner, kitty_id, experience: 10 }.into());
+
+		// a lent kitty is fed by its borrower
+		assert_ok!(KittiesModule::lend(RuntimeOrigin::signed(owner), kitty_id, borrower, 10));
+		assert_noop!(
+			KittiesModule::feed(RuntimeOrigin::signed(owner), kitty_id),
+			Error::<Test>::NotOwner
+		);
+		assert_ok!(KittiesModule::feed(RuntimeOrigin::signed(borrower), kitty_id));
+		assert_eq!(
+			KittiesModule::kitty_progress(kitty_id),
+			Some(Progress { experience: 20, level: 2 })
+		);
+	});
+}
+
+#[test]
+fn breeding_gives_parents_experience() {
+	build_and_execute(|| {
+		let account_id = 1;
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(account_id), 0, 1, *b"abcdabcd"));
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(account_id), 0, 1, *b"abcdabcd"));
+
+		for parent in [0, 1] {
+			assert_eq!(
+				KittiesModule::kitty_progress(parent),
+				Some(Progress { experience: 10, level: 2 })
+			);
+		}
+		assert_eq!(KittiesModule::kitty_progress(2), Some(Progress::default()));
+		assert_eq!(KittiesModule::kitty_info(3).unwrap().progress, Progress::default());
+	});
+}
+
+#[test]
+fn migration_starts_kitties_at_level_one() {
+	build_and_execute(|| {
+		let account_id = 1;
+		Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+
+		// simulate a chain that predates progression
+		let _ = crate::KittyProgress::<Test>::clear(u32::MAX, None);
+		StorageVersion::new(3).put::<KittiesModule>();
+
+		crate::migrations::v4::migrate::<Test>();
+
+		assert_eq!(KittiesModule::on_chain_storage_version(), 4);
+		for kitty_id in 0..2 {
+			assert_eq!(KittiesModule::kitty_progress(kitty_id), Some(Progress::default()));
+		}
+	});
+}
+
+#[test]
+fn battles_resolve_from_stats_and_luck() {
+	let weak = Stats::from_dna(&[0; 16]);
+	let strong = Stats::from_dna(&[0xff; 16]);
+	assert_eq!(weak, Stats { attack: 1, defense: 1, speed: 1 });
+	assert_eq!(strong, Stats { attack: 511, defense: 511, speed: 511 });
+
+	// luck can't make up for a large difference in stats
+	for luck in [[0; 16], [0xff; 16], [7; 16]] {
+		assert_eq!(resolve(&strong, &weak, luck), Winner::Challenger);
+		assert_eq!(resolve(&weak, &strong, luck), Winner::Opponent);
+	}
+
+	// but decides between kitties of similar stats
+	let even = Stats { attack: 100, defense: 100, speed: 100 };
+	let mut luck = [0; 16];
+	luck[0] = 200;
+	assert_eq!(resolve(&even, &even, luck), Winner::Challenger);
+	luck[1] = 201;
+	assert_eq!(resolve(&even, &even, luck), Winner::Opponent);
+
+	// ties go to the faster kitty, then to the opponent
+	let fast = Stats { attack: 100, defense: 99, speed: 101 };
+	assert_eq!(resolve(&fast, &even, [0; 16]), Winner::Challenger);
+	assert_eq!(resolve(&even, &fast, [0; 16]), Winner::Opponent);
+	assert_eq!(resolve(&even, &even, [0; 16]), Winner::Opponent);
+}
+
+#[test]
+fn it_works_for_challenge() {
+	build_and_execute(|| {
+		let (challenger, defender) = (1, 2);
+		let (kitty_id, opponent) = (0, 1);
+		let (challenge_id, stake) = (0, 1_000);
+		Balances::set_balance(RuntimeOrigin::root(), challenger, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), defender, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(challenger), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(defender), *b"abcdabcd"));
+		assert_ok!(KittiesModule::force_set_dna(RuntimeOrigin::root(), kitty_id, [0; 16]));
+		assert_ok!(KittiesModule::force_set_dna(RuntimeOrigin::root(), opponent, [0xff; 16]));
+
+		assert_noop!(
+			KittiesModule::challenge(RuntimeOrigin::signed(defender), kitty_id, opponent, stake),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			KittiesModule::challenge(RuntimeOrigin::signed(challenger), kitty_id, kitty_id, stake),
+			Error::<Test>::SameKittyId
+		);
+		assert_noop!(
+			KittiesModule::challenge(RuntimeOrigin::signed(challenger), kitty_id, 2, stake),
+			Error::<Test>::InvalidKittyId
+		);
+
+		//success
+		assert_ok!(KittiesModule::challenge(
+			RuntimeOrigin::signed(challenger),
+			kitty_id,
+			opponent,
+			stake
+		));
+		assert_eq!(Balances::reserved_balance(challenger), stake);
+		System::assert_last_event(
+			Event::ChallengeIssued {
+				who: challenger,
+				challenge_id,
+				kitty_id,
+				opponent,
+				stake,
+				expires: 11,
+			}
+			.into(),
+		);
+
+		assert_noop!(
+			KittiesModule::accept_challenge(RuntimeOrigin::signed(challenger), challenge_id),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			KittiesModule::accept_challenge(RuntimeOrigin::signed(defender), challenge_id + 1),
+			Error::<Test>::InvalidChallengeId
+		);
+
+		let (challenger_balance, defender_balance) =
+			(Balances::free_balance(challenger), Balances::free_balance(defender));
+		assert_ok!(KittiesModule::accept_challenge(RuntimeOrigin::signed(defender), challenge_id));
+		System::assert_last_event(
+			Event::ChallengeAccepted { who: defender, challenge_id, battle_at: 2 }.into(),
+		);
+		assert_eq!(Balances::reserved_balance(defender), stake);
+		assert_noop!(
+			KittiesModule::accept_challenge(RuntimeOrigin::signed(defender), challenge_id),
+			Error::<Test>::ChallengeAccepted
+		);
+
+		// the far stronger opponent wins the challenger's stake in the next block
+		run_to_block(2);
+		assert_eq!(Balances::reserved_balance(challenger), 0);
+		assert_eq!(Balances::reserved_balance(defender), 0);
+		assert_eq!(Balances::free_balance(challenger), challenger_balance);
+		assert_eq!(Balances::free_balance(defender), defender_balance + stake);
+		assert_eq!(KittiesModule::battle_records(kitty_id), BattleRecord { wins: 0, losses: 1 });
+		assert_eq!(KittiesModule::battle_records(opponent), BattleRecord { wins: 1, losses: 0 });
+		assert_eq!(KittiesModule::challenges(challenge_id), None);
+		System::assert_last_event(
+			Event::BattleResolved { challenge_id, winner: opponent, loser: kitty_id, stake }.into(),
+		);
+	});
+}
+
+#[test]
+fn challenges_expire_and_refund() {
+	build_and_execute(|| {
+		let (challenger, defender, bystander) = (1, 2, 3);
+		let (kitty_id, opponent) = (0, 1);
+		let stake = 1_000;
+		Balances::set_balance(RuntimeOrigin::root(), challenger, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), defender, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(challenger), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(defender), *b"abcdabcd"));
+
+		// the challenger can withdraw at any time
+		assert_ok!(KittiesModule::challenge(
+			RuntimeOrigin::signed(challenger),
+			kitty_id,
+			opponent,
+			stake
+		));
+		assert_ok!(KittiesModule::refund_challenge(RuntimeOrigin::signed(challenger), 0));
+		assert_eq!(Balances::reserved_balance(challenger), 0);
+		System::assert_last_event(Event::ChallengeRefunded { challenge_id: 0 }.into());
+
+		// anyone else has to wait for the challenge to expire
+		assert_ok!(KittiesModule::challenge(
+			RuntimeOrigin::signed(challenger),
+			kitty_id,
+			opponent,
+			stake
+		));
+		assert_noop!(
+			KittiesModule::refund_challenge(RuntimeOrigin::signed(bystander), 1),
+			Error::<Test>::ChallengeNotExpired
+		);
+
+		run_to_block(11);
+		assert_noop!(
+			KittiesModule::accept_challenge(RuntimeOrigin::signed(defender), 1),
+			Error::<Test>::ChallengeExpired
+		);
+		assert_ok!(KittiesModule::refund_challenge(RuntimeOrigin::signed(bystander), 1));
+		assert_eq!(Balances::reserved_balance(challenger), 0);
+		assert_eq!(KittiesModule::challenges(1), None);
+	});
+}
+
+#[test]
+fn accepting_a_challenge_does_not_settle_the_battle() {
+	build_and_execute(|| {
+		let (challenger, defender) = (1, 2);
+		let (kitty_id, opponent) = (0, 1);
+		let (challenge_id, stake) = (0, 1_000);
+		Balances::set_balance(RuntimeOrigin::root(), challenger, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), defender, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(challenger), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(defender), *b"abcdabcd"));
+		assert_ok!(KittiesModule::challenge(
+			RuntimeOrigin::signed(challenger),
+			kitty_id,
+			opponent,
+			stake
+		));
+		assert_ok!(KittiesModule::accept_challenge(RuntimeOrigin::signed(defender), challenge_id));
+
+		// both stakes stay reserved and nothing is recorded until the next block
+		assert_eq!(Balances::reserved_balance(challenger), stake);
+		assert_eq!(Balances::reserved_balance(defender), stake);
+		assert!(KittiesModule::challenges(challenge_id).is_some());
+		assert_eq!(KittiesModule::challenge_acceptor(challenge_id), Some(defender));
+		assert_eq!(KittiesModule::pending_battles(2).into_inner(), vec![challenge_id]);
+		assert_eq!(KittiesModule::battle_records(kitty_id), BattleRecord::default());
+		assert_eq!(KittiesModule::battle_records(opponent), BattleRecord::default());
+		assert!(!System::events().iter().any(|record| matches!(
+			record.event,
+			RuntimeEvent::KittiesModule(Event::BattleResolved { .. })
+		)));
+		// an accepted challenge can no longer be withdrawn
+		assert_noop!(
+			KittiesModule::refund_challenge(RuntimeOrigin::signed(challenger), challenge_id),
+			Error::<Test>::ChallengeAccepted
+		);
+
+		run_to_block(2);
+		assert_eq!(KittiesModule::challenges(challenge_id), None);
+		assert_eq!(KittiesModule::challenge_acceptor(challenge_id), None);
+		assert_eq!(Balances::reserved_balance(challenger), 0);
+		assert_eq!(Balances::reserved_balance(defender), 0);
+		let record = KittiesModule::battle_records(kitty_id);
+		assert_eq!(record.wins + record.losses, 1);
+	});
+}
+
+#[test]
+fn battles_are_refunded_when_a_kitty_is_burned_before_the_fight() {
+	build_and_execute(|| {
+		let (challenger, defender) = (1, 2);
+		let (kitty_id, opponent) = (0, 1);
+		let (challenge_id, stake) = (0, 1_000);
+		Balances::set_balance(RuntimeOrigin::root(), challenger, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), defender, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(challenger), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(defender), *b"abcdabcd"));
+		assert_ok!(KittiesModule::challenge(
+			RuntimeOrigin::signed(challenger),
+			kitty_id,
+			opponent,
+			stake
+		));
+		assert_ok!(KittiesModule::accept_challenge(RuntimeOrigin::signed(defender), challenge_id));
+		assert_ok!(KittiesModule::force_burn(RuntimeOrigin::root(), opponent));
+
+		run_to_block(2);
+		System::assert_last_event(Event::ChallengeRefunded { challenge_id }.into());
+		assert_eq!(Balances::reserved_balance(challenger), 0);
+		assert_eq!(Balances::reserved_balance(defender), 0);
+		assert_eq!(KittiesModule::challenges(challenge_id), None);
+	});
+}
+
+#[test]
+fn battles_pay_out_what_is_left_of_a_slashed_stake() {
+	build_and_execute(|| {
+		let (challenger, defender) = (1, 2);
+		let (kitty_id, opponent) = (0, 1);
+		let (challenge_id, stake) = (0, 1_000);
+		Balances::set_balance(RuntimeOrigin::root(), challenger, 1_000_000_000, 0).unwrap();
+		Balances::set_balance(RuntimeOrigin::root(), defender, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(challenger), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(defender), *b"abcdabcd"));
+		assert_ok!(KittiesModule::force_set_dna(RuntimeOrigin::root(), kitty_id, [0; 16]));
+		assert_ok!(KittiesModule::force_set_dna(RuntimeOrigin::root(), opponent, [0xff; 16]));
+		assert_ok!(KittiesModule::challenge(
+			RuntimeOrigin::signed(challenger),
+			kitty_id,
+			opponent,
+			stake
+		));
+		assert_ok!(KittiesModule::accept_challenge(RuntimeOrigin::signed(defender), challenge_id));
+
+		// part of the loser's stake is slashed elsewhere before the fight
+		let _ = Balances::slash_reserved(&challenger, stake / 2);
+		let defender_balance = Balances::free_balance(defender);
+
+		run_to_block(2);
+		assert_eq!(Balances::reserved_balance(challenger), 0);
+		assert_eq!(Balances::reserved_balance(defender), 0);
+		assert_eq!(Balances::free_balance(defender), defender_balance + stake + stake / 2);
+		System::assert_last_event(
+			Event::BattleResolved { challenge_id, winner: opponent, loser: kitty_id, stake }.into(),
+		);
+	});
+}
+
+#[test]
+fn it_works_for_fractionalize() {
+	build_and_execute(|| {
+		let kitty_id = 0;
+		let (owner, collector) = (1, 2);
+		Balances::set_balance(RuntimeOrigin::root(), owner, 1_000_000_000, 0).unwrap();
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(owner), *b"abcdabcd"));
+
+		assert_noop!(
+			KittiesModule::fractionalize(RuntimeOrigin::signed(collector), kitty_id, 100),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			KittiesModule::fractionalize(RuntimeOrigin::signed(owner), kitty_id, 0),
+			Error::<Test>::InvalidShares
+		);
+
+		//success
+		assert_ok!(KittiesModule::fractionalize(RuntimeOrigin::signed(owner), kitty_id, 100));
+		let pallet_account_id: u64 = KittyPalletId::get().into_account_truncating();
+		assert_eq!(KittiesModule::kitty_owner(kitty_id), Some(pallet_account_id));
+		assert_eq!(KittiesModule::kitty_fractions(kitty_id), Some(100));
+		assert_eq!(KittiesModule::kitty_shares(kitty_id, owner), 100);
+		System::assert_last_event(
+			Event::KittyFractionalized { who: owner, kitty_id, shares: 100 }.into(),
+		);
+
+		// the kitty is out of play while fractionalized
+		assert_noop!(
+			KittiesModule::transfer(RuntimeOrigin::signed(owner), collector, kitty_id),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			KittiesModule::sale(RuntimeOrigin::signed(owner), kitty_id),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			KittiesModule::breed(RuntimeOrigin::signed(owner), kitty_id, 1, *b"abcdabcd"),
+			refunded(Error::<Test>::KittyFractionalized)
+		);
+		assert_noop!(
+			KittiesModule::fractionalize(RuntimeOrigin::signed(owner), kitty_id, 100),
+			Error::<Test>::NotOwner
+		);
+
+		assert_ok!(KittiesModule::transfer_shares(
+			RuntimeOrigin::signed(owner),
+			kitty_id,
+			collector,
+			40
+		));
+		assert_eq!(KittiesModule::kitty_shares(kitty_id, owner), 60);
+		assert_eq!(KittiesModule::kitty_shares(kitty_id, collector), 40);
+		System::assert_last_event(
+			Event::SharesTransferred { kitty_id, from: owner, to: collector, amount: 40 }.into(),
+		);
+		assert_noop!(
+			KittiesModule::transfer_shares(RuntimeOrigin::signed(collector), kitty_id, owner, 41),
+			Error::<Test>::InsufficientShares
+		);
+		assert_noop!(
+			KittiesModule::redeem(RuntimeOrigin::signed(collector), kitty_id),
+			Error::<Test>::InsufficientShares
+		);
+
+		// whoever gathers all shares takes the kitty
+		assert_ok!(KittiesModule::transfer_shares(
+			RuntimeOrigin::signed(owner),
+			kitty_id,
+			collector,
+			60
+		));
+		assert_ok!(KittiesModule::redeem(RuntimeOrigin::signed(collector), kitty_id));
+		assert_eq!(KittiesModule::kitty_owner(kitty_id), Some(collector));
+		assert_eq!(KittiesModule::kitty_fractions(kitty_id), None);
+		assert_eq!(KittiesModule::kitty_shares(kitty_id, collector), 0);
+		System::assert_last_event(Event::KittyRedeemed { who: collector, kitty_id }.into());
+		assert_noop!(
+			KittiesModule::redeem(RuntimeOrigin::signed(collector), kitty_id),
+			Error::<Test>::NotFractionalized
+		);
+	});
+}
+
+#[test]
+fn genesis_kitties_are_created_and_listed() {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> {
+		kitties: vec![(1, [1; 16], *b"genesis1"), (2, [2; 16], *b"genesis2")],
+		on_sale: vec![1],
+		rarity_committers: vec![3],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	sp_io::TestExternalities::new(storage).execute_with(|| {
+		assert_eq!(KittiesModule::next_kitty_id(), 2);
+		assert_eq!(
+			KittiesModule::kitties(1),
+			Some(crate::Kitty { dna: [2; 16], name: *b"genesis2" })
+		);
+		assert_eq!(KittiesModule::kitty_owner(0), Some(1));
+		assert_eq!(KittiesModule::kitty_owner(1), Some(2));
+		assert_eq!(KittiesModule::kitty_on_sale(0), None);
+		assert_eq!(KittiesModule::kitty_on_sale(1), Some(()));
+		assert_eq!(KittiesModule::rarity_committer(3), Some(()));
+		for kitty_id in 0..2 {
+			assert_eq!(KittiesModule::kitty_collection(kitty_id), 0);
+			assert_eq!(KittiesModule::kitty_progress(kitty_id), Some(Progress::default()));
+		}
+		KittiesModule::do_try_state().expect("kitty storage invariants hold");
+	});
+}
+
+#[test]
+fn rarity_scores_favour_rare_traits() {
+	assert_eq!(rarity_scores(&[]), vec![]);
+	// Every trait of the single kitty is shared by all kitties.
+	assert_eq!(rarity_scores(&[(7, [0; 16])]), vec![(7, 400)]);
+	// Two kitties show every trait value of the first, one shows those of the last.
+	assert_eq!(
+		rarity_scores(&[(0, [0; 16]), (1, [0; 16]), (2, [1; 16])]),
+		vec![(0, 600), (1, 600), (2, 1200)]
+	);
+	// Only the trait bytes of the DNA count.
+	let mut dna = [0; 16];
+	dna[0] = 1;
+	assert_eq!(rarity_scores(&[(0, [0; 16]), (1, dna)]), vec![(0, 400), (1, 400)]);
+}
+
+#[test]
+fn merkle_root_commits_to_every_leaf() {
+	let (a, b, c) = (rarity_leaf(0, 600), rarity_leaf(1, 600), rarity_leaf(2, 1200));
+	let pair = |left: [u8; 32], right: [u8; 32]| blake2_256(&[left, right].concat());
+
+	assert_eq!(merkle_root(vec![]), [0; 32]);
+	assert_eq!(merkle_root(vec![a]), a);
+	assert_eq!(merkle_root(vec![a, b]), pair(a, b));
+	// The odd leaf moves up unchanged.
+	assert_eq!(merkle_root(vec![a, b, c]), pair(pair(a, b), c));
+	assert_ne!(merkle_root(vec![b, a, c]), merkle_root(vec![a, b, c]));
+	assert_ne!(merkle_root(vec![a, b, rarity_leaf(2, 1201)]), merkle_root(vec![a, b, c]));
+}
+
+#[test]
+fn offchain_worker_stores_scores_and_submits_root() {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> {
+		kitties: vec![
+			(1, [0; 16], *b"common01"),
+			(2, [0; 16], *b"common02"),
+			(3, [1; 16], *b"rare0001"),
+		],
+		on_sale: vec![],
+		rarity_committers: vec![1],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	let (offchain, _) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	UintAuthorityId::set_all_keys(vec![1u64]);
+
+	ext.execute_with(|| {
+		System::set_block_number(5);
+		KittiesModule::offchain_worker(5);
+
+		let scores: Vec<(KittyId, u64)> = vec![(0, 600), (1, 600), (2, 1200)];
+		assert_eq!(
+			StorageValueRef::persistent(RARITY_SCORES_KEY).get::<(u64, Vec<(KittyId, u64)>)>(),
+			Ok(Some((5, scores.clone())))
+		);
+
+		let tx = pool_state.write().transactions.pop().expect("a rarity root is submitted");
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = UncheckedExtrinsic::decode(&mut &tx[..]).unwrap();
+		assert_eq!(tx.signature, None);
+		match tx.function {
+			RuntimeCall::KittiesModule(crate::Call::submit_rarity_root { payload, signature }) => {
+				let leaves = scores.iter().map(|(id, score)| rarity_leaf(*id, *score)).collect();
+				assert_eq!(
+					payload,
+					RarityPayload {
+						block_number: 5,
+						root: merkle_root(leaves),
+						kitty_count: 3,
+						public: UintAuthorityId(1),
+					}
+				);
+				assert!(SignedPayload::<Test>::verify::<TestAuthId>(&payload, signature));
+			},
+			call => panic!("unexpected call {:?}", call),
+		}
+
+		// Nothing is submitted between intervals.
+		System::set_block_number(6);
+		KittiesModule::offchain_worker(6);
+		assert!(pool_state.read().transactions.is_empty());
+		KittiesModule::do_try_state().expect("kitty storage invariants hold");
+	});
+}
+
+#[test]
+fn rarity_roots_are_validated_and_committed() {
+	build_and_execute(|| {
+		let signed = |block_number: u64, signer: u64| {
+			let payload = RarityPayload {
+				block_number,
+				root: [7; 32],
+				kitty_count: 3,
+				public: UintAuthorityId(1),
+			};
+			let signature = UintAuthorityId(signer).sign(&payload.encode()).unwrap();
+			(payload, signature)
+		};
+		let validate = |(payload, signature)| {
+			KittiesModule::validate_unsigned(
+				TransactionSource::External,
+				&crate::Call::submit_rarity_root { payload, signature },
+			)
+		};
+		System::set_block_number(5);
+
+		assert_eq!(validate(signed(5, 1)), InvalidTransaction::BadSigner.into());
+		assert_noop!(
+			KittiesModule::set_rarity_committer(RuntimeOrigin::signed(1), 1, true),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(KittiesModule::set_rarity_committer(RuntimeOrigin::root(), 1, true));
+		System::assert_last_event(Event::RarityCommitterSet { who: 1, allowed: true }.into());
+
+		assert_eq!(validate(signed(5, 2)), InvalidTransaction::BadProof.into());
+		assert_eq!(validate(signed(6, 1)), InvalidTransaction::Future.into());
+		assert_eq!(validate(signed(5, 1)).map(|valid| valid.priority), Ok(100));
+
+		let (payload, signature) = signed(5, 1);
+		assert_noop!(
+			KittiesModule::submit_rarity_root(
+				RuntimeOrigin::signed(1),
+				payload.clone(),
+				signature.clone()
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(KittiesModule::submit_rarity_root(RuntimeOrigin::none(), payload, signature));
+		System::assert_last_event(
+			Event::RarityRootCommitted { block_number: 5, root: [7; 32], kitty_count: 3 }.into(),
+		);
+		assert_eq!(
+			KittiesModule::rarity_root(),
+			Some(RarityCommitment { block_number: 5, root: [7; 32], kitty_count: 3 })
+		);
+		assert_eq!(KittiesModule::next_rarity_at(), 10);
+
+		// The interval is covered until block 10.
+		System::set_block_number(10);
+		assert_eq!(validate(signed(9, 1)), InvalidTransaction::Stale.into());
+		let (payload, signature) = signed(9, 1);
+		assert_noop!(
+			KittiesModule::submit_rarity_root(RuntimeOrigin::none(), payload, signature),
+			Error::<Test>::StaleRarityRoot
+		);
+		assert_ok!(validate(signed(10, 1)));
+
+		assert_ok!(KittiesModule::set_rarity_committer(RuntimeOrigin::root(), 1, false));
+		assert_eq!(KittiesModule::rarity_committer(1), None);
+		let (payload, signature) = signed(10, 1);
+		assert_noop!(
+			KittiesModule::submit_rarity_root(RuntimeOrigin::none(), payload, signature),
+			Error::<Test>::NotRarityCommitter
+		);
+	});
+}
+
+/// Breed the family 0 + 1 -> 3, 1 + 2 -> 4 and 3 + 4 -> 5 for account 1.
+fn breed_family() {
+	let account_id = 1;
+	Balances::set_balance(RuntimeOrigin::root(), account_id, 1_000_000_000, 0).unwrap();
+	for _ in 0..3 {
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(account_id), *b"abcdabcd"));
+	}
+	for (parent_1, parent_2) in [(0, 1), (1, 2), (3, 4)] {
+		assert_ok!(KittiesModule::breed(
+			RuntimeOrigin::signed(account_id),
+			parent_1,
+			parent_2,
+			*b"abcdabcd"
+		));
+	}
+}
+
+#[test]
+fn family_tree_walks_ancestors_and_descendants() {
+	build_and_execute(|| {
+		breed_family();
+		let relative = |kitty_id, generation, parents| Relative { kitty_id, generation, parents };
+
+		let tree = KittiesModule::family_tree(5, 8).unwrap();
+		assert_eq!(tree.parents, Some((3, 4)));
+		// 1 is a grandparent through both parents, but listed once
+		assert_eq!(
+			tree.ancestors,
+			vec![
+				relative(3, 1, Some((0, 1))),
+				relative(4, 1, Some((1, 2))),
+				relative(0, 2, None),
+				relative(1, 2, None),
+				relative(2, 2, None),
+			]
+		);
+		assert_eq!(tree.descendants, vec![]);
+		assert!(!tree.truncated);
+
+		let tree = KittiesModule::family_tree(1, 1).unwrap();
+		assert_eq!(tree.ancestors, vec![]);
+		assert_eq!(
+			tree.descendants,
+			vec![relative(3, 1, Some((0, 1))), relative(4, 1, Some((1, 2)))]
+		);
+		assert_eq!(KittiesModule::family_tree(1, 2).unwrap().descendants.len(), 3);
+		assert_eq!(KittiesModule::family_tree(6, 8), None);
+
+		// burned kitties leave the children of their parents, but stay parents of their children
+		assert_ok!(KittiesModule::force_burn(RuntimeOrigin::root(), 5));
+		assert_eq!(KittiesModule::kitty_children(3, 5), None);
+		assert_eq!(KittiesModule::family_tree(3, 8).unwrap().descendants, vec![]);
+		assert_ok!(KittiesModule::force_burn(RuntimeOrigin::root(), 1));
+		assert_eq!(KittiesModule::kitty_children(1, 3), Some(()));
+		assert_eq!(
+			KittiesModule::family_tree(3, 8).unwrap().ancestors,
+			vec![relative(0, 1, None), relative(1, 1, None)]
+		);
+		assert_eq!(KittiesModule::family_tree(1, 8), None);
+	});
+}
+
+#[test]
+fn common_ancestor_is_the_nearest_shared_kitty() {
+	build_and_execute(|| {
+		breed_family();
+		let common = |kitty_id, generations_from_first, generations_from_second| {
+			Some(CommonAncestor { kitty_id, generations_from_first, generations_from_second })
+		};
+
+		assert_eq!(KittiesModule::common_ancestor(3, 4), common(1, 1, 1));
+		assert_eq!(KittiesModule::common_ancestor(5, 0), common(0, 2, 0));
+		assert_eq!(KittiesModule::common_ancestor(4, 5), common(4, 0, 1));
+		assert_eq!(KittiesModule::common_ancestor(3, 3), common(3, 0, 0));
+		assert_eq!(KittiesModule::common_ancestor(0, 2), None);
+		assert_eq!(KittiesModule::common_ancestor(0, 6), None);
+	});
+}
+
+#[test]
+fn family_trees_are_bounded() {
+	// a single line of descent, `n` the only child of `n - 1`
+	let line = |kitty_id: KittyId| kitty_id.checked_sub(1).map(|parent| (parent, parent));
+	let tree = walk_family_tree(100, u32::MAX, line, |_| core::iter::empty());
+	assert_eq!(tree.ancestors.len() as u32, MAX_FAMILY_DEPTH);
+	assert_eq!(tree.ancestors.last().unwrap().kitty_id, 100 - MAX_FAMILY_DEPTH);
+	assert!(!tree.truncated);
+
+	// a kitty with a thousand children
+	let tree =
+		walk_family_tree(0, 1, |_| None, |kitty_id| (1..=1_000).filter(move |_| kitty_id == 0));
+	assert_eq!(tree.descendants.len() as u32, MAX_FAMILY_NODES);
+	assert!(tree.truncated);
+
+	assert_eq!(
+		find_common_ancestor(40, 60, line),
+		Some(CommonAncestor {
+			kitty_id: 40,
+			generations_from_first: 0,
+			generations_from_second: 20
+		})
+	);
+	// 0 is beyond the generations searched from 60
+	assert_eq!(find_common_ancestor(0, 60, line), None);
+}
+
+#[test]
+fn migration_indexes_children() {
+	build_and_execute(|| {
+		breed_family();
+
+		// simulate a chain that predates the children index
+		let _ = crate::KittyChildren::<Test>::clear(u32::MAX, None);
+		StorageVersion::new(4).put::<KittiesModule>();
+
+		crate::migrations::v5::migrate::<Test>();
+
+		assert_eq!(KittiesModule::on_chain_storage_version(), 5);
+		for (parent, child) in [(0, 3), (1, 3), (1, 4), (2, 4), (3, 5), (4, 5)] {
+			assert_eq!(KittiesModule::kitty_children(parent, child), Some(()));
+		}
+		assert_eq!(crate::KittyChildren::<Test>::iter().count(), 6);
+	});
 }
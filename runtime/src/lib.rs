@@ -15,7 +15,7 @@ use sp_runtime::{
 	traits::{
 		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
 };
 use sp_std::prelude::*;
@@ -38,7 +38,9 @@ pub use frame_support::{
 	StorageValue,
 };
 pub use frame_system::Call as SystemCall;
+use frame_system::EnsureRoot;
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_kitties::Call as KittiesCall;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
 #[cfg(any(feature = "std", test))]
@@ -104,7 +106,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 300,
+	spec_version: 324,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -272,6 +274,13 @@ impl pallet_template::Config for Runtime {
 parameter_types! {
 	pub KittyPalletId: PalletId = PalletId(*b"py/kitty");
 	pub KittyPrice: Balance = EXISTENTIAL_DEPOSIT * 10;
+	pub SwapDeposit: Balance = EXISTENTIAL_DEPOSIT;
+	pub MintPrice: Balance = EXISTENTIAL_DEPOSIT * 10;
+	pub BreedPrice: Balance = EXISTENTIAL_DEPOSIT * 10;
+	pub KittyDepositPerByte: Balance = EXISTENTIAL_DEPOSIT / 10;
+	pub FeedFee: Balance = EXISTENTIAL_DEPOSIT;
+	pub LevelThresholds: Vec<u32> = sp_std::vec![100, 300, 600, 1_000, 1_500];
+	pub const RarityPriority: TransactionPriority = TransactionPriority::max_value() / 2;
 }
 
 /// Configure the pallet-kitties in pallets/template.
@@ -280,7 +289,46 @@ impl pallet_kitties::Config for Runtime {
 	type Randomness = RandomnessModule;
 	type Currency = Balances;
 	type KittyPrice = KittyPrice;
+	type MintPrice = MintPrice;
+	type BreedPrice = BreedPrice;
+	type PriceCurve = pallet_kitties::LinearCurve;
 	type PalletId = KittyPalletId;
+	type MaxSwapsPerKitty = ConstU32<16>;
+	type SwapDeposit = SwapDeposit;
+	type MaxLoansPerBlock = ConstU32<64>;
+	type FreezeOrigin = EnsureRoot<AccountId>;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type MaxSupply = ConstU32<1_000_000>;
+	type MaxMintsPerAccountPerPeriod = ConstU32<10>;
+	type MintPeriod = ConstU32<DAYS>;
+	type MaxCollectionMetadataLen = ConstU32<256>;
+	type MaxMetadataLen = ConstU32<128>;
+	type MaxAttributeKeyLen = ConstU32<32>;
+	type MaxAttributeValueLen = ConstU32<128>;
+	type MaxAttributesPerKitty = ConstU32<32>;
+	type DepositPerByte = KittyDepositPerByte;
+	type FeedFee = FeedFee;
+	type FeedExperience = ConstU32<10>;
+	type BreedExperience = ConstU32<25>;
+	type LevelThresholds = LevelThresholds;
+	type ChallengePeriod = ConstU32<DAYS>;
+	type MaxBattlesPerBlock = ConstU32<64>;
+	type AuthorityId = pallet_kitties::crypto::RarityAuthId;
+	type RarityInterval = ConstU32<HOURS>;
+	type RarityPriority = RarityPriority;
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	RuntimeCall: From<C>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = UncheckedExtrinsic;
 }
 
 impl pallet_insecure_randomness_collective_flip::Config for Runtime {}
@@ -514,6 +562,46 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_kitties_runtime_api::KittiesApi<Block, AccountId, Balance> for Runtime {
+		fn kitty(
+			kitty_id: pallet_kitties::KittyId,
+		) -> Option<pallet_kitties::KittyInfo<AccountId>> {
+			KittiesModule::kitty_info(kitty_id)
+		}
+
+		fn next_kitty_id() -> pallet_kitties::KittyId {
+			KittiesModule::next_kitty_id()
+		}
+
+		fn remaining_supply() -> u32 {
+			KittiesModule::remaining_supply()
+		}
+
+		fn mint_price() -> Balance {
+			KittiesModule::mint_price()
+		}
+
+		fn breed_price() -> Balance {
+			KittiesModule::breed_price()
+		}
+	}
+
+	impl pallet_kitties_runtime_api::KittyFamilyApi<Block> for Runtime {
+		fn family_tree(
+			kitty_id: pallet_kitties::KittyId,
+			depth: u32,
+		) -> Option<pallet_kitties::FamilyTree> {
+			KittiesModule::family_tree(kitty_id, depth)
+		}
+
+		fn common_ancestor(
+			first: pallet_kitties::KittyId,
+			second: pallet_kitties::KittyId,
+		) -> Option<pallet_kitties::CommonAncestor> {
+			KittiesModule::common_ancestor(first, second)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (